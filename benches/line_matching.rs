@@ -0,0 +1,62 @@
+//! Matching a log against the line patterns one pattern at a time and in a single pass, run with
+//! `cargo bench --bench line_matching`
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+// the crate is a binary, the patterns are pulled in as a module of the bench instead
+#[allow(dead_code)]
+#[path = "../src/patterns.rs"]
+mod patterns;
+
+use patterns::LogPatterns;
+
+const LINES: usize = 100_000;
+
+/// Log shaped like a busy trading session, most lines are chat and debug output matching none of
+/// the patterns
+fn synthetic_log(lines: usize) -> String {
+    let prefix = |i: usize| format!("2025/01/31 21:04:05 {i} cffb0719 [INFO Client 1234]");
+    (0..lines)
+        .map(|i| match i % 100 {
+            0 => format!("{} Generating level 15 area \"G1_5\" with seed {i}", prefix(i)),
+            1 => format!("{} Connecting to instance server at 10.0.0.1:6112", prefix(i)),
+            2 => format!("{} : Player{} has joined the area.", prefix(i), i % 7),
+            3 => format!("{} : Player{} has left the area.", prefix(i), i % 7),
+            4 => format!("{} : Player{} (Witch) is now level {}", prefix(i), i % 7, i % 100),
+            5 => format!("{} @From Buyer{i}: Hi, I would like to buy your Exalted Orb", prefix(i)),
+            6..=30 => format!("{} #Trader{i}: WTS 10 Divine Orbs, whisper me", prefix(i)),
+            _ => format!("{} [SHADER] Delay: ON, loaded {i} shaders", prefix(i)),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn line_matching(c: &mut Criterion) {
+    let patterns = LogPatterns::new(&[]).unwrap();
+    let log = synthetic_log(LINES);
+
+    let mut group = c.benchmark_group("line_matching");
+    group.throughput(Throughput::Elements(LINES as u64));
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            black_box(&log)
+                .lines()
+                .filter(|line| patterns.captures_sequential(line).is_some())
+                .count()
+        })
+    });
+    group.bench_function("single_pass", |b| {
+        b.iter(|| {
+            black_box(&log)
+                .lines()
+                .filter(|line| patterns.match_line(line).first_captures().is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, line_matching);
+criterion_main!(benches);
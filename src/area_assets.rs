@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::models::MapChangeInfo;
+
+#[derive(Debug, Deserialize)]
+struct AreaAssetRule {
+    /// Regex matched against the area code, e.g. ^Hideout or ^MapUberBoss_
+    pattern: String,
+    image: String,
+    text: Option<String>,
+}
+
+/// Custom discord assets for areas, uploaded to your own discord application
+#[derive(Debug, Clone)]
+pub struct AreaAssets {
+    rules: Vec<(Regex, String, Option<String>)>,
+}
+
+impl AreaAssets {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let rules: Vec<AreaAssetRule> = serde_json::from_str(&fs::read_to_string(path)?)
+            .with_context(|| format!("Invalid area assets file {path:?}"))?;
+
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern)
+                    .with_context(|| format!("Invalid area asset pattern {:?}", rule.pattern))?;
+                Ok((regex, rule.image, rule.text))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn get_images(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|(_, image, _)| image.as_str())
+    }
+
+    /// Image and hover text of the first rule matching the area, the text defaults to the area name
+    pub fn get_asset(&self, area_info: &MapChangeInfo) -> Option<(String, String)> {
+        self.rules
+            .iter()
+            .find(|(regex, ..)| regex.is_match(&area_info.code))
+            .map(|(_, image, text)| (image.clone(), text.clone().unwrap_or(area_info.name.clone())))
+    }
+}
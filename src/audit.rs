@@ -0,0 +1,61 @@
+use std::collections::BTreeSet;
+
+use anyhow::bail;
+use serde::Deserialize;
+
+use crate::area_assets::AreaAssets;
+use crate::game::Game;
+use crate::models::CharacterClass;
+
+#[derive(Debug, Deserialize)]
+struct UploadedAsset {
+    name: String,
+}
+
+/// Every image key the presence can show for the game, class and ascendency art plus the
+/// configured area assets
+fn get_expected_assets(game: Game, area_assets: Option<&AreaAssets>) -> BTreeSet<String> {
+    let mut assets = BTreeSet::new();
+    for class in CharacterClass::get_classes(game) {
+        assets.insert(game.get_discord_image_name(class.get_discord_image_name()));
+        for ascd in class.get_ascendencies(game) {
+            assets.insert(game.get_discord_image_name(ascd.get_discord_image_name()));
+        }
+    }
+    if let Some(area_assets) = area_assets {
+        assets.extend(area_assets.get_images().map(str::to_owned));
+    }
+    assets
+}
+
+/// Checks the expected image keys against the assets uploaded to the discord application,
+/// failing if any are missing
+pub fn audit_assets(
+    game: Game,
+    client_id: &str,
+    area_assets: Option<&AreaAssets>,
+) -> anyhow::Result<()> {
+    let url = format!("https://discord.com/api/v10/oauth2/applications/{client_id}/assets");
+    let uploaded: Vec<UploadedAsset> = ureq::get(&url)
+        .set("User-Agent", concat!("poe2-rpc/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(|e| anyhow::anyhow!("Failed to fetch the assets of application {client_id}: {e}"))?
+        .into_json()?;
+    let uploaded: BTreeSet<String> = uploaded.into_iter().map(|asset| asset.name).collect();
+
+    let expected = get_expected_assets(game, area_assets);
+    let missing: Vec<_> = expected.difference(&uploaded).collect();
+    println!(
+        "{} of {} assets uploaded to application {client_id}",
+        expected.len() - missing.len(),
+        expected.len()
+    );
+    for name in &missing {
+        println!("missing: {name}");
+    }
+
+    if !missing.is_empty() {
+        bail!("{} assets are missing", missing.len());
+    }
+    Ok(())
+}
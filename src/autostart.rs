@@ -0,0 +1,78 @@
+#[cfg(windows)]
+const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+#[cfg(windows)]
+const VALUE_NAME: &str = "poe2-discord-rpc";
+
+#[cfg(windows)]
+fn quote_arg(arg: &str) -> String {
+    match arg.is_empty() || arg.contains([' ', '\t', '"']) {
+        true => format!("\"{}\"", arg.replace('"', "\\\"")),
+        false => arg.to_string(),
+    }
+}
+
+/// Command line that starts the tool with the given flags from the current working directory,
+/// so relative paths given as flags (export directory, game log, ...) keep resolving to the same
+/// place
+#[cfg(windows)]
+fn get_autostart_command(args: &[String]) -> anyhow::Result<String> {
+    use std::env;
+
+    let exe = env::current_exe()?;
+    let cwd = env::current_dir()?;
+
+    let mut command = format!(
+        "cmd.exe /c start \"\" /d {} {}",
+        quote_arg(&cwd.to_string_lossy()),
+        quote_arg(&exe.to_string_lossy())
+    );
+    for arg in args {
+        command.push(' ');
+        command.push_str(&quote_arg(arg));
+    }
+    Ok(command)
+}
+
+/// Registers the tool to start with the flags it was started with, minus the subcommand
+pub fn install(subcommand: &str) -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).filter(|arg| arg != subcommand).collect();
+    install_with_args(&args)
+}
+
+#[cfg(windows)]
+pub fn install_with_args(args: &[String]) -> anyhow::Result<()> {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    let command = get_autostart_command(args)?;
+    let (key, _) = RegKey::predef(HKEY_CURRENT_USER).create_subkey(RUN_KEY)?;
+    key.set_value(VALUE_NAME, &command)?;
+    log::info!("Registered autostart: {command}");
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn uninstall() -> anyhow::Result<()> {
+    use std::io::ErrorKind;
+
+    use winreg::enums::{HKEY_CURRENT_USER, KEY_SET_VALUE};
+    use winreg::RegKey;
+
+    let key = RegKey::predef(HKEY_CURRENT_USER).open_subkey_with_flags(RUN_KEY, KEY_SET_VALUE)?;
+    match key.delete_value(VALUE_NAME) {
+        Ok(()) => log::info!("Removed autostart"),
+        Err(e) if e.kind() == ErrorKind::NotFound => log::info!("Autostart was not registered"),
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn install_with_args(_args: &[String]) -> anyhow::Result<()> {
+    anyhow::bail!("Autostart is only supported on windows")
+}
+
+#[cfg(not(windows))]
+pub fn uninstall() -> anyhow::Result<()> {
+    anyhow::bail!("Autostart is only supported on windows")
+}
@@ -0,0 +1,31 @@
+use std::env;
+use std::process::{Command, Stdio};
+
+/// Set on the relaunched copy so it doesn't detach again
+const DETACHED_ENV: &str = "POE2_DRPC_DETACHED";
+
+pub fn is_detached() -> bool {
+    env::var_os(DETACHED_ENV).is_some()
+}
+
+/// Relaunches the tool with the same arguments as a background process without a console window
+pub fn detach() -> anyhow::Result<u32> {
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .args(env::args_os().skip(1))
+        .env(DETACHED_ENV, "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        const DETACHED_PROCESS: u32 = 0x00000008;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NO_WINDOW);
+    }
+
+    Ok(command.spawn()?.id())
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tokio::task::{self, JoinHandle};
+
+use crate::events::{EventBus, GameEvent};
+use crate::game::Game;
+use crate::models::ClassInfo;
+use crate::state::{self, SessionState};
+use crate::supervisor;
+
+/// The character window api is rate limited, don't ask more often than this
+const MIN_FETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Session cookie for the character api, kept out of the logs
+#[derive(Clone)]
+pub struct Poesessid(String);
+
+impl FromStr for Poesessid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "" => Err("POESESSID must not be empty".to_string()),
+            s => Ok(Self(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Debug for Poesessid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Poesessid(..)")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CharacterApi {
+    pub game: Game,
+    pub account_name: String,
+    pub poesessid: Poesessid,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiCharacter {
+    name: String,
+    league: String,
+    class: String,
+    level: u16,
+    #[serde(default)]
+    last_active: bool,
+}
+
+impl CharacterApi {
+    fn fetch_characters(&self) -> anyhow::Result<Vec<ApiCharacter>> {
+        let url = format!("{}/character-window/get-characters", self.game.get_website_url());
+        Ok(ureq::get(&url)
+            .query("accountName", &self.account_name)
+            .set("User-Agent", concat!("poe2-rpc/", env!("CARGO_PKG_VERSION")))
+            .set("Cookie", &format!("POESESSID={}", self.poesessid.0))
+            .call()?
+            .into_json()?)
+    }
+
+    /// Events updating the session with the character currently played, which is the one the
+    /// log last saw or otherwise the last active one on the account
+    fn get_character_events(&self, current: Option<&ClassInfo>) -> anyhow::Result<Vec<GameEvent>> {
+        let characters = self.fetch_characters()?;
+        let Some(character) = current
+            .and_then(|current| characters.iter().find(|c| c.name == current.username))
+            .or_else(|| characters.iter().find(|c| c.last_active))
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut events = Vec::new();
+        match ClassInfo::from_parts(&character.name, &character.class, character.level) {
+            Some(class_info) if current != Some(&class_info) => {
+                events.push(GameEvent::CharacterUpdated(class_info))
+            },
+            _ => {},
+        }
+        events.push(GameEvent::League { name: character.league.clone() });
+        Ok(events)
+    }
+}
+
+pub fn spawn_character_fetcher(
+    api: CharacterApi,
+    bus: &EventBus,
+    state: Arc<Mutex<SessionState>>,
+) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+    let bus = bus.clone();
+
+    tokio::spawn(supervisor::run("character api", async move || -> anyhow::Result<()> {
+        let mut last_fetch: Option<Instant> = None;
+
+        while let Some(event) = rx.recv().await {
+            let is_session_start = matches!(event, GameEvent::SessionStarted { .. });
+            if !is_session_start && !matches!(event, GameEvent::AreaChanged(_)) {
+                continue;
+            }
+            if !is_session_start && last_fetch.is_some_and(|t| t.elapsed() < MIN_FETCH_INTERVAL) {
+                continue;
+            }
+            last_fetch = Some(Instant::now());
+
+            let current = state::lock(&state).character.clone();
+            match task::block_in_place(|| api.get_character_events(current.as_ref())) {
+                Ok(events) => {
+                    for event in events {
+                        state::lock(&state).apply(&event);
+                        bus.publish(event);
+                    }
+                },
+                Err(e) => log::warn!("Failed to fetch characters: {e}"),
+            }
+        }
+        Ok(())
+    }))
+}
@@ -0,0 +1,100 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::Context;
+use clap::parser::ValueSource;
+use clap::{Arg, Command};
+
+/// Flags applied on every start, one per line with its value after the first space, e.g.
+/// `--game-dir C:\Games\Path of Exile 2`, flags passed on the command line take precedence (see
+/// [`without_overridden`])
+pub const CONFIG_FILE: &str = "poe2-drpc.cfg";
+
+pub fn load_args(path: &Path) -> anyhow::Result<Vec<String>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {path:?}")),
+    };
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .flat_map(|line| match line.split_once(' ') {
+            Some((flag, value)) => vec![flag.to_string(), value.trim().to_string()],
+            None => vec![line.to_string()],
+        })
+        .collect())
+}
+
+fn find_arg<'a>(command: &'a Command, flag: &str) -> Option<&'a Arg> {
+    let flag = flag.split('=').next()?;
+    match flag.strip_prefix("--") {
+        Some(long) => command.get_arguments().find(|arg| arg.get_long() == Some(long)),
+        None => {
+            let short = flag.strip_prefix('-')?.chars().next()?;
+            command.get_arguments().find(|arg| arg.get_short() == Some(short))
+        },
+    }
+}
+
+/// Config flags minus those the command line passes again or conflicts with, so the ones on the
+/// command line take precedence instead of adding up or failing to parse
+pub fn without_overridden(
+    command: &Command,
+    config_args: Vec<String>,
+    cli_args: &[OsString],
+) -> Vec<String> {
+    let Ok(matches) = command.clone().ignore_errors(true).try_get_matches_from(cli_args) else {
+        return config_args;
+    };
+    let on_cli: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+        .collect();
+    let conflict = |a: &Arg, b: &Arg| command.get_arg_conflicts_with(a).contains(&b);
+    let is_overridden = |arg: &Arg| {
+        on_cli
+            .iter()
+            .any(|&cli| cli.get_id() == arg.get_id() || conflict(cli, arg) || conflict(arg, cli))
+    };
+
+    // a flag with the values following it, until the next known flag
+    let mut flags: Vec<(Option<&Arg>, Vec<String>)> = Vec::new();
+    for arg in config_args {
+        match find_arg(command, &arg) {
+            Some(found) if arg.starts_with('-') => flags.push((Some(found), vec![arg])),
+            _ => match flags.last_mut() {
+                Some((_, values)) => values.push(arg),
+                None => flags.push((None, vec![arg])),
+            },
+        }
+    }
+    flags
+        .into_iter()
+        .filter(|(arg, _)| arg.is_none_or(|arg| !is_overridden(arg)))
+        .flat_map(|(_, args)| args)
+        .collect()
+}
+
+/// Writes the flags to the config file, expects values to directly follow their flag
+pub fn save_args(path: &Path, args: &[String]) -> anyhow::Result<()> {
+    let mut lines = vec!["# poe2-rpc flags, one per line".to_string()];
+    for arg in args {
+        match lines.last_mut() {
+            Some(line) if !arg.starts_with("--") => {
+                line.push(' ');
+                line.push_str(arg);
+            },
+            _ => lines.push(arg.clone()),
+        }
+    }
+    lines.push(String::new());
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, lines.join("\n")).with_context(|| format!("Failed to write {path:?}"))
+}
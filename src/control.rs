@@ -0,0 +1,144 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::events::{EventBus, GameEvent};
+use crate::state::{self, SessionState};
+
+#[cfg(windows)]
+pub const PIPE_NAME: &str = r"\\.\pipe\poe2-drpc";
+
+/// Shared with the rest of the tool, commands act on the same presence the discord client shows
+#[derive(Debug, Clone)]
+pub struct Control {
+    pub hidden: Arc<AtomicBool>,
+    pub bus: EventBus,
+    pub state: Arc<Mutex<SessionState>>,
+}
+
+impl Control {
+    fn emit(&self, event: GameEvent) {
+        state::lock(&self.state).apply(&event);
+        self.bus.publish(event);
+    }
+
+    /// Runs a single command line, e.g. `pause` or `set-note back in 5`, returning the reply
+    fn handle_command(&self, line: &str) -> String {
+        let (verb, arg) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        match verb {
+            "pause" => {
+                self.hidden.store(true, Ordering::Relaxed);
+                "ok".to_string()
+            },
+            "resume" => {
+                self.hidden.store(false, Ordering::Relaxed);
+                "ok".to_string()
+            },
+            "status" => json!({
+                "hidden": self.hidden.load(Ordering::Relaxed),
+                "session": *state::lock(&self.state),
+            })
+            .to_string(),
+            "set-note" => {
+                let note = Some(arg.trim().to_string()).filter(|note| !note.is_empty());
+                self.emit(GameEvent::NoteChanged { note });
+                "ok".to_string()
+            },
+            "clear-note" => {
+                self.emit(GameEvent::NoteChanged { note: None });
+                "ok".to_string()
+            },
+            _ => format!(
+                "error: unknown command '{verb}', expected one of: pause, resume, status, \
+                 set-note <text>, clear-note"
+            ),
+        }
+    }
+
+    async fn handle_client(&self, stream: impl AsyncRead + AsyncWrite) -> std::io::Result<()> {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            log::debug!("Control command: {line}");
+            let reply = self.handle_command(&line);
+            writer.write_all(format!("{reply}\n").as_bytes()).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+pub fn spawn_control_server(control: Control) -> anyhow::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new().first_pipe_instance(true).create(PIPE_NAME)?;
+    log::info!("Accepting control commands on {PIPE_NAME}");
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = server.connect().await {
+                log::warn!("Failed to accept control client: {e}");
+                continue;
+            }
+
+            let client = server;
+            server = match ServerOptions::new().create(PIPE_NAME) {
+                Ok(server) => server,
+                Err(e) => {
+                    log::warn!("Control pipe stopped: {e}");
+                    return;
+                },
+            };
+
+            let control = control.clone();
+            tokio::spawn(async move {
+                if let Err(e) = control.handle_client(client).await {
+                    log::debug!("Control client disconnected: {e}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn spawn_control_server(control: Control) -> anyhow::Result<()> {
+    use std::os::unix::net::UnixStream;
+
+    use tokio::net::UnixListener;
+
+    let path = std::env::temp_dir().join("poe2-drpc.sock");
+    if UnixStream::connect(&path).is_ok() {
+        anyhow::bail!("Another instance is already accepting control commands on {path:?}");
+    }
+    // nobody is listening, the socket was left behind by a run that didn't shut down cleanly
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    log::info!("Accepting control commands on {path:?}");
+
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("Control socket stopped: {e}");
+                    return;
+                },
+            };
+
+            let control = control.clone();
+            tokio::spawn(async move {
+                if let Err(e) = control.handle_client(stream).await {
+                    log::debug!("Control client disconnected: {e}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
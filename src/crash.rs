@@ -0,0 +1,106 @@
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, TryLockError};
+use std::thread;
+
+use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+use futures_util::FutureExt;
+use tokio::task;
+
+use crate::state::SessionState;
+
+/// Number of recently matched log lines included in crash reports
+const RECENT_LINES: usize = 50;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Remembers a log line that matched one of the patterns for a later crash report
+pub fn record_line(line: &str) {
+    let Ok(mut recent) = RECENT.lock() else {
+        return;
+    };
+    if recent.len() >= RECENT_LINES {
+        recent.pop_front();
+    }
+    recent.push_back(line.trim_end().to_string());
+}
+
+/// Clears the activity over a fresh ipc connection, discord keys activities by process so this
+/// also removes the one the discord client worker set
+pub fn clear_presence(discord_client_id: &str) {
+    let clear = || -> Result<(), Box<dyn std::error::Error>> {
+        let mut rpc = DiscordIpcClient::new(discord_client_id)?;
+        rpc.connect()?;
+        rpc.clear_activity()?;
+        rpc.close()?;
+        Ok(())
+    };
+    match clear() {
+        Ok(()) => log::info!("Cleared the discord activity"),
+        Err(e) => log::warn!("Failed to clear the discord activity: {e}"),
+    }
+}
+
+fn write_report(
+    data_dir: &Path,
+    info: &PanicHookInfo,
+    state: &Mutex<SessionState>,
+) -> anyhow::Result<PathBuf> {
+    let mut report = String::new();
+    writeln!(report, "poe2-rpc {} crashed at {}", env!("CARGO_PKG_VERSION"), chrono::Local::now())?;
+    writeln!(report, "thread '{}' {info}", thread::current().name().unwrap_or("<unnamed>"))?;
+    writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture())?;
+
+    writeln!(report, "last matched log lines:")?;
+    if let Ok(recent) = RECENT.lock() {
+        recent.iter().try_for_each(|line| writeln!(report, "{line}"))?;
+    }
+
+    // the panicking thread may hold the state lock itself, waiting on it would never return
+    let state = match state.try_lock() {
+        Ok(state) => serde_json::to_string_pretty(&*state)?,
+        Err(TryLockError::Poisoned(e)) => serde_json::to_string_pretty(&*e.into_inner())?,
+        Err(TryLockError::WouldBlock) => "<locked by the crashing thread>".to_string(),
+    };
+    writeln!(report, "\nsession state:\n{state}")?;
+
+    let path = data_dir
+        .join(format!("poe2-drpc-crash-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Writes a crash report with the backtrace, the last matched log lines and the session state to
+/// the data directory on panics, including those a supervisor recovers from
+pub fn install_panic_hook(data_dir: PathBuf, state: Arc<Mutex<SessionState>>) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        match write_report(&data_dir, info, &state) {
+            Ok(path) => log::error!("Wrote a crash report to {path:?}"),
+            Err(e) => log::error!("Failed to write a crash report: {e}"),
+        }
+    }));
+}
+
+/// Runs the tool's main future, clearing the discord activity before a panic escaping it ends the
+/// tool so the presence doesn't linger
+pub async fn clear_presence_on_panic<F: Future>(
+    discord_client_id: Option<&str>,
+    f: F,
+) -> F::Output {
+    match AssertUnwindSafe(f).catch_unwind().await {
+        Ok(output) => output,
+        Err(panic) => {
+            if let Some(id) = discord_client_id {
+                task::block_in_place(|| clear_presence(id));
+            }
+            panic::resume_unwind(panic)
+        },
+    }
+}
@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+use tokio::task::{self, JoinHandle};
+
+use crate::events::{EventBus, GameEvent};
+use crate::presence::Presence;
+use crate::state::{self, SessionState};
+use crate::supervisor;
+
+/// How often time based presence state and deferred activity updates are re-evaluated
+const TICK_INTERVAL: Duration = Duration::from_millis(500);
+
+fn set_connected(state: &Mutex<SessionState>, is_connected: &mut bool, connected: bool) {
+    *is_connected = connected;
+    state::lock(state).discord_connected = connected;
+}
+
+/// Owns the discord ipc connection, connecting while the game runs and keeping the activity in
+/// sync with the session. The ipc client is blocking, its calls run on a blocking-capable worker
+/// without holding the session state lock so they never hold up log reading. The activity is
+/// cleared while `hidden` is set.
+pub fn spawn_discord_client(
+    mut rpc: DiscordIpcClient,
+    mut presence: Presence,
+    min_activity_interval: Duration,
+    hidden: Arc<AtomicBool>,
+    bus: &EventBus,
+    state: Arc<Mutex<SessionState>>,
+) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+    let mut in_session = false;
+    let mut is_connected = false;
+    let mut is_hidden = false;
+    let mut is_dirty = false;
+    let mut last_activity_update: Option<Instant> = None;
+
+    tokio::spawn(supervisor::run(
+        "discord client",
+        async move || -> Result<(), Box<dyn std::error::Error>> {
+            // the connection is in an unknown state after a failure, reconnect from scratch
+            if is_connected {
+                let _ = task::block_in_place(|| rpc.close());
+                set_connected(&state, &mut is_connected, false);
+            }
+
+            loop {
+                let event = match tokio::time::timeout(TICK_INTERVAL, rx.recv()).await {
+                    Ok(Some(event)) => Some(event),
+                    Ok(None) => return Ok(()),
+                    Err(_) => None,
+                };
+
+                let pending = std::iter::from_fn(|| rx.try_recv().ok());
+                for event in event.into_iter().chain(pending) {
+                    presence.on_event(&event);
+                    match event {
+                        GameEvent::SessionStarted { .. } => in_session = true,
+                        GameEvent::SessionEnded { .. } => {
+                            in_session = false;
+                            if is_connected {
+                                task::block_in_place(|| rpc.clear_activity())?;
+                                log::trace!("Cleared activity");
+                                task::block_in_place(|| rpc.close())?;
+                                set_connected(&state, &mut is_connected, false);
+                                log::trace!("Disconnected from discord rpc");
+                            }
+                        },
+                        GameEvent::LevelUp { .. }
+                        | GameEvent::CharacterUpdated(_)
+                        | GameEvent::AreaChanged(_)
+                        | GameEvent::CharacterSelect { .. }
+                        | GameEvent::Whisper { is_trade: true, .. }
+                        | GameEvent::GameVersion { .. }
+                        | GameEvent::ServerConnected { .. }
+                        | GameEvent::PlayerJoined { .. }
+                        | GameEvent::PlayerLeft { .. }
+                        | GameEvent::League { .. }
+                        | GameEvent::NoteChanged { .. } => is_dirty = true,
+                        _ => {},
+                    }
+                }
+
+                if !in_session {
+                    continue;
+                }
+                if !is_connected {
+                    task::block_in_place(|| rpc.connect())?;
+                    set_connected(&state, &mut is_connected, true);
+                    log::trace!("Connected to discord rpc");
+                    is_dirty = true;
+                }
+
+                if hidden.load(Ordering::Relaxed) != is_hidden {
+                    is_hidden = !is_hidden;
+                    match is_hidden {
+                        true => {
+                            task::block_in_place(|| rpc.clear_activity())?;
+                            log::info!("Presence hidden");
+                        },
+                        false => {
+                            is_dirty = true;
+                            log::info!("Presence shown");
+                        },
+                    }
+                }
+                if is_hidden {
+                    continue;
+                }
+
+                // the state lock is released before the ipc call, emitting events waits on it
+                let activity = {
+                    let state = state::lock(&state);
+                    is_dirty |= presence.tick(&state);
+
+                    let can_update =
+                        last_activity_update.is_none_or(|t| t.elapsed() >= min_activity_interval);
+                    if !is_dirty || !can_update {
+                        continue;
+                    }
+                    is_dirty = false;
+                    last_activity_update = Some(Instant::now());
+                    log::info!(
+                        "Updating activity {{ class: {:#?}, instance: {:#?} }}",
+                        state.character,
+                        state.area
+                    );
+                    presence.build_activity(&state)
+                };
+                task::block_in_place(|| rpc.set_activity(activity))?;
+            }
+        },
+    ))
+}
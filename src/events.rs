@@ -0,0 +1,52 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::models::{ClassInfo, MapChangeInfo};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    SessionStarted { ts: i64, character: Option<ClassInfo> },
+    SessionEnded { ts: i64 },
+    LevelUp { character: ClassInfo, ts: i64 },
+    // character details from outside the log (the character api), not a level up
+    CharacterUpdated(ClassInfo),
+    AreaChanged(MapChangeInfo),
+    CharacterSelect { ts: i64 },
+    PlayerJoined { username: String },
+    PlayerLeft { username: String },
+    Death { username: String, ts: i64 },
+    Whisper { from: String, message: String, is_trade: bool },
+    GameVersion { version: String },
+    ServerConnected { address: String, gateway: Option<String>, latency_ms: Option<u32> },
+    League { name: String },
+    NoteChanged { note: Option<String> },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<UnboundedSender<GameEvent>>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> UnboundedReceiver<GameEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscribers.lock().unwrap_or_else(PoisonError::into_inner).push(tx);
+        rx
+    }
+
+    pub fn publish(&self, event: GameEvent) {
+        log::trace!("Publishing event: {event:?}");
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// Drops all subscriber channels, ending their receive loops once drained
+    pub fn close(&self) {
+        self.subscribers.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+}
@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{Local, TimeZone};
+use clap::ValueEnum;
+use serde::Serialize;
+use tokio::task::{self, JoinHandle};
+
+use crate::events::{EventBus, GameEvent};
+use crate::summary::SessionSummary;
+use crate::supervisor;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// Session wide part of the summary, csv exports write it to its own file next to the visits
+#[derive(Serialize)]
+struct SummaryTotals<'a> {
+    started_at: i64,
+    ended_at: Option<i64>,
+    character: Option<&'a str>,
+    start_level: Option<u16>,
+    end_level: Option<u16>,
+    levels_gained: u32,
+    deaths: u32,
+    trade_whispers: u32,
+    areas_visited: usize,
+}
+
+impl<'a> SummaryTotals<'a> {
+    fn new(summary: &'a SessionSummary) -> Self {
+        Self {
+            started_at: summary.started_at,
+            ended_at: summary.ended_at,
+            character: summary.character.as_deref(),
+            start_level: summary.start_level,
+            end_level: summary.end_level,
+            levels_gained: summary.levels_gained,
+            deaths: summary.deaths,
+            trade_whispers: summary.trade_whispers,
+            areas_visited: summary.visits.len(),
+        }
+    }
+}
+
+/// Writes the summary, returning the written files
+pub fn write_summary(
+    summary: &SessionSummary,
+    dir: &Path,
+    format: ExportFormat,
+) -> anyhow::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+
+    let started_at = Local
+        .timestamp_opt(summary.started_at, 0)
+        .single()
+        .context("Invalid session start time")?;
+    let name = format!("session-{}", started_at.format("%Y%m%d-%H%M%S"));
+    let path = dir.join(format!("{name}.{}", format.extension()));
+
+    match format {
+        ExportFormat::Json => {
+            fs::write(&path, serde_json::to_string_pretty(summary)?)?;
+            Ok(vec![path])
+        },
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_path(&path)?;
+            for visit in &summary.visits {
+                writer.serialize(visit)?;
+            }
+            writer.flush()?;
+
+            let totals_path = dir.join(format!("{name}-summary.csv"));
+            let mut writer = csv::Writer::from_path(&totals_path)?;
+            writer.serialize(SummaryTotals::new(summary))?;
+            writer.flush()?;
+            Ok(vec![path, totals_path])
+        },
+    }
+}
+
+pub fn spawn_exporter(dir: PathBuf, format: ExportFormat, bus: &EventBus) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("session exporter", async move || -> anyhow::Result<()> {
+        let mut summary = SessionSummary::default();
+
+        while let Some(event) = rx.recv().await {
+            summary.apply(&event);
+
+            if let GameEvent::SessionEnded { .. } = event {
+                match task::block_in_place(|| write_summary(&summary, &dir, format)) {
+                    Ok(paths) => log::info!("Exported session summary to {paths:?}"),
+                    Err(e) => log::warn!("Failed to export session summary: {e}"),
+                }
+            }
+        }
+        Ok(())
+    }))
+}
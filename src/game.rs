@@ -0,0 +1,71 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum Game {
+    Poe1,
+    Poe2,
+}
+
+impl Game {
+    pub fn get_process_names(&self) -> &'static [&'static str] {
+        match self {
+            Self::Poe1 => &[
+                "PathOfExile_x64Steam.exe",
+                "PathOfExile_x64.exe",
+                "PathOfExileSteam.exe",
+                "PathOfExile.exe",
+                "PathOfExile_x64_KG.exe",
+                "PathOfExile_KG.exe",
+            ],
+            Self::Poe2 => &[
+                "PathOfExile_x64Steam.exe",
+                "PathOfExile_x64.exe",
+                "PathOfExileSteam.exe",
+                "PathOfExile.exe",
+            ],
+        }
+    }
+
+    pub fn get_default_directories(&self) -> &'static [&'static str] {
+        match self {
+            Self::Poe1 => &[
+                "C:\\Program Files (x86)\\Grinding Gear Games\\Path of Exile",
+                "C:\\Program Files (x86)\\Steam\\steamapps\\common\\Path of Exile",
+            ],
+            Self::Poe2 => &[
+                "C:\\Program Files (x86)\\Grinding Gear Games\\Path of Exile 2",
+                "C:\\Program Files (x86)\\Steam\\steamapps\\common\\Path of Exile 2",
+            ],
+        }
+    }
+
+    pub fn get_default_translations(&self) -> &'static str {
+        match self {
+            Self::Poe1 => include_str!("../resources/translations_poe1_en.json"),
+            Self::Poe2 => include_str!("../resources/translations_en.json"),
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Poe1 => "Path of Exile",
+            Self::Poe2 => "Path of Exile 2",
+        }
+    }
+
+    pub fn get_website_url(&self) -> &'static str {
+        match self {
+            Self::Poe1 => "https://www.pathofexile.com",
+            Self::Poe2 => "https://pathofexile2.com",
+        }
+    }
+
+    /// Both games share class names, poe1 art is uploaded with a prefix to keep them apart
+    pub fn get_discord_image_name(&self, name: &str) -> String {
+        match self {
+            Self::Poe1 => format!("poe1_{name}"),
+            Self::Poe2 => name.to_owned(),
+        }
+    }
+}
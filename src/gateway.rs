@@ -0,0 +1,40 @@
+/// Login gateways by the address prefix of their login servers, which are named after the gateway
+/// (e.g. fra.login.pathofexile.com). Instance servers are only logged by ip and aren't mapped, the
+/// session keeps the gateway picked at login while connected to them.
+const GATEWAYS: &[(&str, &str)] = &[
+    ("tx.", "Texas"),
+    ("wdc.", "Washington, D.C."),
+    ("ca.", "California"),
+    ("can.", "Canada"),
+    ("lon.", "London"),
+    ("fra.", "Frankfurt"),
+    ("ams.", "Amsterdam"),
+    ("par.", "Paris"),
+    ("mil.", "Milan"),
+    ("mos.", "Moscow"),
+    ("sgp.", "Singapore"),
+    ("au.", "Australia"),
+    ("jp.", "Japan"),
+    ("sa.", "South Africa"),
+    ("bra.", "Brazil"),
+];
+
+/// Name of the gateway a login server address belongs to
+pub fn get_gateway_name(address: &str) -> Option<&'static str> {
+    GATEWAYS
+        .iter()
+        .find(|(prefix, _)| address.to_lowercase().starts_with(prefix))
+        .map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_login_servers_only() {
+        assert_eq!(get_gateway_name("FRA.login.pathofexile.com"), Some("Frankfurt"));
+        assert_eq!(get_gateway_name("wdc.login.pathofexile2.com"), Some("Washington, D.C."));
+        assert_eq!(get_gateway_name("198.51.100.23"), None);
+    }
+}
@@ -0,0 +1,413 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use eframe::egui;
+
+use crate::game::Game;
+use crate::models::{
+    AreaKind, ClassAscendency, ClassInfo, MapChangeInfo, PresenceStrings, StringOverride,
+};
+use crate::presence::{Presence, PresenceOptions, ProfileLink};
+use crate::state::SessionState;
+use crate::{autostart, config};
+
+/// Flags editable in the settings window, turned back into a command line to start the tool with
+#[derive(Debug, Default)]
+struct Settings {
+    game: Option<Game>,
+    game_dir: String,
+    translations_file: String,
+    whisper_counter: bool,
+    map_counter: bool,
+    show_game_version: bool,
+    show_gateway: bool,
+    show_ping: bool,
+    show_seed: bool,
+    party_hint: bool,
+    level_bracket: u16,
+    account_name: String,
+    profile_button: Option<ProfileLink>,
+    strings: PresenceStrings,
+    /// Flags of the config file the window doesn't manage, saved back unchanged
+    other_args: Vec<String>,
+}
+
+impl Settings {
+    fn from_args(args: &[String]) -> Self {
+        let mut settings = Self::default();
+        let mut args = args.iter().peekable();
+        while let Some(flag) = args.next() {
+            let mut value = || args.next_if(|arg| !arg.starts_with("--")).cloned();
+            match flag.as_str() {
+                "--game" => settings.game = value().and_then(|v| Game::from_str(&v, true).ok()),
+                // further game directories follow more instances, the window edits the first
+                "--game-dir" if settings.game_dir.is_empty() => {
+                    settings.game_dir = value().unwrap_or_default()
+                },
+                "--translations-file" => settings.translations_file = value().unwrap_or_default(),
+                "--whisper-counter" => settings.whisper_counter = true,
+                "--map-counter" => settings.map_counter = true,
+                "--show-game-version" => settings.show_game_version = true,
+                "--show-gateway" => settings.show_gateway = true,
+                "--show-ping" => settings.show_ping = true,
+                "--show-seed" => settings.show_seed = true,
+                "--party-hint" => settings.party_hint = true,
+                "--level-bracket" => {
+                    settings.level_bracket = value().and_then(|v| v.parse().ok()).unwrap_or(0)
+                },
+                "--account-name" => settings.account_name = value().unwrap_or_default(),
+                "--profile-button" => {
+                    settings.profile_button =
+                        value().and_then(|v| ProfileLink::from_str(&v, true).ok())
+                },
+                "--presence-string" => {
+                    let overrides: Vec<StringOverride> =
+                        value().and_then(|v| v.parse().ok()).into_iter().collect();
+                    settings.strings.apply_overrides(&overrides);
+                },
+                _ => {
+                    settings.other_args.push(flag.clone());
+                    settings.other_args.extend(value());
+                },
+            }
+        }
+        settings
+    }
+
+    fn get_game(&self) -> Game {
+        self.game.unwrap_or(Game::Poe2)
+    }
+
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut push = |flag: &str, value: Option<String>| {
+            args.push(flag.to_string());
+            args.extend(value);
+        };
+
+        if self.get_game() == Game::Poe1 {
+            push("--game", Some("poe1".to_string()));
+        }
+        if !self.game_dir.is_empty() {
+            push("--game-dir", Some(self.game_dir.clone()));
+        }
+        if !self.translations_file.is_empty() {
+            push("--translations-file", Some(self.translations_file.clone()));
+        }
+        for (enabled, flag) in [
+            (self.whisper_counter, "--whisper-counter"),
+            (self.map_counter, "--map-counter"),
+            (self.show_game_version, "--show-game-version"),
+            (self.show_gateway, "--show-gateway"),
+            (self.show_ping, "--show-ping"),
+            (self.show_seed, "--show-seed"),
+            (self.party_hint, "--party-hint"),
+        ] {
+            if enabled {
+                push(flag, None);
+            }
+        }
+        if self.level_bracket > 1 {
+            push("--level-bracket", Some(self.level_bracket.to_string()));
+        }
+        if !self.account_name.is_empty() {
+            push("--account-name", Some(self.account_name.clone()));
+            match self.profile_button {
+                Some(ProfileLink::Profile) => push("--profile-button", Some("profile".to_string())),
+                Some(ProfileLink::Ninja) => push("--profile-button", Some("ninja".to_string())),
+                None => {},
+            }
+        }
+        let mut strings = self.strings.clone();
+        let mut defaults = PresenceStrings::default();
+        for ((name, template), (_, default)) in
+            strings.fields_mut().into_iter().zip(defaults.fields_mut())
+        {
+            if template != default {
+                push("--presence-string", Some(format!("{name}={template}")));
+            }
+        }
+        args.extend(self.other_args.iter().cloned());
+        args
+    }
+
+    fn get_presence_options(&self) -> PresenceOptions {
+        PresenceOptions {
+            game: self.get_game(),
+            trade_state: false,
+            trade_burst: 2,
+            trade_quiet_period: Duration::from_secs(120),
+            whisper_counter: self.whisper_counter,
+            map_counter: self.map_counter,
+            game_version: self.show_game_version,
+            gateway: self.show_gateway,
+            ping: self.show_ping,
+            seed: self.show_seed,
+            party_hint: self.party_hint,
+            level_bracket: Some(self.level_bracket),
+            profile_button: self
+                .profile_button
+                .filter(|_| !self.account_name.is_empty())
+                .map(|link| (link, self.account_name.clone())),
+            area_assets: None,
+            strings: self.strings.clone(),
+            splits: false,
+        }
+    }
+}
+
+/// Session the preview is rendered for
+fn get_sample_state() -> SessionState {
+    let mut state = SessionState::new();
+    state.game_running = true;
+    state.game_version = Some("0.2.0g".to_string());
+    state.gateway = Some("Frankfurt".to_string());
+    state.latency_ms = Some(45);
+    state.stats.trade_whispers = 3;
+    state.stats.maps_completed = 14;
+    state.set_character(
+        &ClassInfo {
+            class: ClassAscendency::Deadeye.get_class(),
+            ascendency: Some(ClassAscendency::Deadeye),
+            username: "ExampleRanger".to_string(),
+            level: 93,
+        },
+        chrono::Utc::now().timestamp(),
+    );
+    state.set_area(&MapChangeInfo {
+        level: 79,
+        code: "MapSwampTower".to_string(),
+        name: "Sinking Spire".to_string(),
+        kind: AreaKind::Map,
+        seed: 123456789,
+        ts: chrono::Utc::now().timestamp(),
+    });
+    state.player_joined("ExampleFriend");
+    state
+}
+
+struct SettingsApp {
+    config_file: PathBuf,
+    settings: Settings,
+    sample_state: SessionState,
+    status: String,
+}
+
+impl SettingsApp {
+    fn show_preview(&self, ui: &mut egui::Ui) {
+        let presence = Presence::new(self.settings.get_presence_options());
+        let activity = presence.build_activity(&self.sample_state);
+        let activity = serde_json::to_value(&activity).unwrap_or_default();
+
+        for (label, value) in [
+            ("Details", &activity["details"]),
+            ("State", &activity["state"]),
+            ("Large image", &activity["assets"]["large_image"]),
+            ("Large text", &activity["assets"]["large_text"]),
+            ("Small image", &activity["assets"]["small_image"]),
+            ("Small text", &activity["assets"]["small_text"]),
+            ("Button", &activity["buttons"][0]["url"]),
+        ] {
+            if let Some(value) = value.as_str() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{label}:"));
+                    ui.monospace(value);
+                });
+            }
+        }
+    }
+
+    /// Flags pointing the tool at the config file, the flags themselves are read from there since
+    /// passing them again would repeat every flag that can be given multiple times
+    fn get_config_args(&self) -> Vec<String> {
+        vec!["--config".to_string(), self.config_file.to_string_lossy().into_owned()]
+    }
+
+    /// Saves the flags and starts the tool with them in the background
+    fn save_and_start(&self, args: &[String]) -> anyhow::Result<()> {
+        config::save_args(&self.config_file, args)?;
+        let exe: PathBuf = env::current_exe()?;
+        Command::new(exe).args(self.get_config_args()).arg("--background").spawn()?;
+        Ok(())
+    }
+
+    fn save_and_register_autostart(&self, args: &[String]) -> anyhow::Result<()> {
+        config::save_args(&self.config_file, args)?;
+        autostart::install_with_args(&self.get_config_args())
+    }
+}
+
+impl eframe::App for SettingsApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let settings = &mut self.settings;
+
+            ui.heading("Game");
+            egui::ComboBox::from_label("Game")
+                .selected_text(settings.get_game().get_name())
+                .show_ui(ui, |ui| {
+                    for game in [Game::Poe2, Game::Poe1] {
+                        ui.selectable_value(&mut settings.game, Some(game), game.get_name());
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label("Game directory");
+                ui.text_edit_singleline(&mut settings.game_dir);
+                if ui.button("Browse").clicked() {
+                    if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                        settings.game_dir = dir.to_string_lossy().into_owned();
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Translations file");
+                ui.text_edit_singleline(&mut settings.translations_file);
+                if ui.button("Browse").clicked() {
+                    if let Some(file) =
+                        rfd::FileDialog::new().add_filter("json", &["json"]).pick_file()
+                    {
+                        settings.translations_file = file.to_string_lossy().into_owned();
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.heading("Presence");
+            ui.checkbox(&mut settings.whisper_counter, "Trade whisper counter");
+            ui.checkbox(&mut settings.map_counter, "Map counter");
+            ui.checkbox(&mut settings.show_game_version, "Game version");
+            ui.checkbox(&mut settings.show_gateway, "Login gateway");
+            ui.checkbox(&mut settings.show_ping, "Ping");
+
+            ui.separator();
+            ui.heading("Privacy");
+            ui.checkbox(&mut settings.show_seed, "Instance seed");
+            ui.checkbox(&mut settings.party_hint, "Party size");
+            ui.horizontal(|ui| {
+                ui.label("Level bracket (0 shows the exact level)");
+                ui.add(egui::DragValue::new(&mut settings.level_bracket).range(0..=50));
+            });
+
+            ui.separator();
+            ui.heading("Buttons");
+            ui.horizontal(|ui| {
+                ui.label("Account name");
+                ui.text_edit_singleline(&mut settings.account_name);
+            });
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut settings.profile_button, None, "No button");
+                ui.radio_value(
+                    &mut settings.profile_button,
+                    Some(ProfileLink::Profile),
+                    "Character profile",
+                );
+                ui.radio_value(&mut settings.profile_button, Some(ProfileLink::Ninja), "poe.ninja");
+            });
+
+            ui.separator();
+            egui::CollapsingHeader::new("Templates").show(ui, |ui| {
+                egui::Grid::new("templates").show(ui, |ui| {
+                    for (name, template) in settings.strings.fields_mut() {
+                        ui.label(name);
+                        ui.text_edit_singleline(template);
+                        ui.end_row();
+                    }
+                });
+            });
+            if !settings.other_args.is_empty() {
+                ui.label(format!("Kept from the config file: {}", settings.other_args.join(" ")));
+            }
+
+            ui.separator();
+            ui.heading("Preview");
+            self.show_preview(ui);
+
+            ui.separator();
+            let args = self.settings.to_args();
+            ui.monospace(format!("poe2-rpc {}", args.join(" ")));
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    self.status = match config::save_args(&self.config_file, &args) {
+                        Ok(()) => format!("Saved to {:?}", self.config_file),
+                        Err(e) => format!("Failed to save: {e}"),
+                    };
+                }
+                if ui.button("Start").clicked() {
+                    self.status = match self.save_and_start(&args) {
+                        Ok(()) => "Saved and started".to_string(),
+                        Err(e) => format!("Failed to start: {e}"),
+                    };
+                }
+                if ui.button("Start with windows").clicked() {
+                    self.status = match self.save_and_register_autostart(&args) {
+                        Ok(()) => "Saved and registered autostart".to_string(),
+                        Err(e) => format!("Failed to register autostart: {e}"),
+                    };
+                }
+                ui.label(&self.status);
+            });
+        });
+    }
+}
+
+pub fn run_settings(config_file: PathBuf) -> anyhow::Result<()> {
+    let settings = Settings::from_args(&config::load_args(&config_file)?);
+    let app = SettingsApp {
+        config_file,
+        settings,
+        sample_state: get_sample_state(),
+        status: String::new(),
+    };
+
+    eframe::run_native(
+        "poe2-rpc settings",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| Ok(Box::new(app))),
+    )
+    .map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_flags_it_does_not_manage() {
+        let args: Vec<String> = [
+            "--game-dir",
+            r"C:\Games\Path of Exile 2",
+            "--webhook-url",
+            "https://discord.com/api/webhooks/1/abc",
+            "--show-ping",
+            "--pattern",
+            r"level_up=: (\w+) \((\w+)\) is level (\d+)",
+            "--presence-string",
+            "ping={ms} ms",
+            "--no-history",
+        ]
+        .map(String::from)
+        .into();
+
+        let settings = Settings::from_args(&args);
+        assert!(settings.show_ping);
+        assert_eq!(settings.strings.ping, "{ms} ms");
+        assert_eq!(
+            settings.to_args(),
+            [
+                "--game-dir",
+                r"C:\Games\Path of Exile 2",
+                "--show-ping",
+                "--presence-string",
+                "ping={ms} ms",
+                "--webhook-url",
+                "https://discord.com/api/webhooks/1/abc",
+                "--pattern",
+                r"level_up=: (\w+) \((\w+)\) is level (\d+)",
+                "--no-history",
+            ]
+        );
+    }
+}
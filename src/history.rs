@@ -0,0 +1,154 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+use tokio::task::{self, JoinHandle};
+
+use crate::events::{EventBus, GameEvent};
+use crate::supervisor;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        id INTEGER PRIMARY KEY,
+        started_at INTEGER NOT NULL,
+        ended_at INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS area_visits (
+        id INTEGER PRIMARY KEY,
+        session_id INTEGER NOT NULL REFERENCES sessions(id),
+        character TEXT,
+        code TEXT NOT NULL,
+        name TEXT NOT NULL,
+        level INTEGER NOT NULL,
+        seed INTEGER NOT NULL,
+        entered_at INTEGER NOT NULL,
+        left_at INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS level_ups (
+        id INTEGER PRIMARY KEY,
+        session_id INTEGER NOT NULL REFERENCES sessions(id),
+        character TEXT NOT NULL,
+        class TEXT NOT NULL,
+        ascendency TEXT,
+        level INTEGER NOT NULL,
+        ts INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS deaths (
+        id INTEGER PRIMARY KEY,
+        session_id INTEGER NOT NULL REFERENCES sessions(id),
+        character TEXT NOT NULL,
+        area TEXT,
+        ts INTEGER NOT NULL
+    );
+";
+
+pub struct History {
+    conn: Connection,
+    session_id: Option<i64>,
+    visit_id: Option<i64>,
+    character: Option<String>,
+    area: Option<String>,
+}
+
+impl History {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn, session_id: None, visit_id: None, character: None, area: None })
+    }
+
+    pub fn record(&mut self, event: &GameEvent) -> rusqlite::Result<()> {
+        match event {
+            GameEvent::SessionStarted { ts, character } => {
+                self.conn.execute("INSERT INTO sessions (started_at) VALUES (?1)", params![ts])?;
+                self.session_id = Some(self.conn.last_insert_rowid());
+                self.visit_id = None;
+                self.character = character.as_ref().map(|c| c.username.clone());
+                self.area = None;
+            },
+            GameEvent::SessionEnded { ts } => {
+                self.leave_area(*ts)?;
+                if let Some(session_id) = self.session_id.take() {
+                    self.conn.execute(
+                        "UPDATE sessions SET ended_at = ?1 WHERE id = ?2",
+                        params![ts, session_id],
+                    )?;
+                }
+            },
+            GameEvent::AreaChanged(area_info) => {
+                let Some(session_id) = self.session_id else { return Ok(()) };
+                self.leave_area(area_info.ts)?;
+                self.conn.execute(
+                    "INSERT INTO area_visits (session_id, character, code, name, level, seed, \
+                     entered_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        session_id,
+                        self.character,
+                        area_info.code,
+                        area_info.name,
+                        area_info.level,
+                        area_info.seed as i64,
+                        area_info.ts
+                    ],
+                )?;
+                self.visit_id = Some(self.conn.last_insert_rowid());
+                self.area = Some(area_info.name.clone());
+            },
+            GameEvent::CharacterSelect { ts } => {
+                self.leave_area(*ts)?;
+                self.character = None;
+                self.area = None;
+            },
+            GameEvent::LevelUp { character: class_info, ts } => {
+                let Some(session_id) = self.session_id else { return Ok(()) };
+                self.conn.execute(
+                    "INSERT INTO level_ups (session_id, character, class, ascendency, level, ts) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        session_id,
+                        class_info.username,
+                        class_info.class.to_string(),
+                        class_info.ascendency.as_ref().map(|a| a.to_string()),
+                        class_info.level,
+                        ts
+                    ],
+                )?;
+                self.character = Some(class_info.username.clone());
+            },
+            GameEvent::CharacterUpdated(class_info) => {
+                self.character = Some(class_info.username.clone());
+            },
+            GameEvent::Death { username, ts } => {
+                let Some(session_id) = self.session_id else { return Ok(()) };
+                self.conn.execute(
+                    "INSERT INTO deaths (session_id, character, area, ts) VALUES (?1, ?2, ?3, ?4)",
+                    params![session_id, username, self.area, ts],
+                )?;
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn leave_area(&mut self, ts: i64) -> rusqlite::Result<()> {
+        if let Some(visit_id) = self.visit_id.take() {
+            self.conn.execute(
+                "UPDATE area_visits SET left_at = ?1 WHERE id = ?2",
+                params![ts, visit_id],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn spawn_recorder(mut history: History, bus: &EventBus) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("history recorder", async move || -> anyhow::Result<()> {
+        while let Some(event) = rx.recv().await {
+            if let Err(e) = task::block_in_place(|| history.record(&event)) {
+                log::warn!("Failed to record {event:?} to history: {e}");
+            }
+        }
+        Ok(())
+    }))
+}
@@ -0,0 +1,93 @@
+use std::str::FromStr;
+
+const MOD_ALT: u32 = 0x0001;
+const MOD_CONTROL: u32 = 0x0002;
+const MOD_SHIFT: u32 = 0x0004;
+const MOD_WIN: u32 = 0x0008;
+
+/// Global hotkey as modifiers and a virtual key code, parsed from e.g. Ctrl+Alt+P or Shift+F9
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct Hotkey {
+    modifiers: u32,
+    key: u32,
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = 0;
+        let mut key = None;
+
+        for part in s.split('+').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= MOD_CONTROL,
+                "alt" => modifiers |= MOD_ALT,
+                "shift" => modifiers |= MOD_SHIFT,
+                "win" | "super" => modifiers |= MOD_WIN,
+                _ if key.is_some() => return Err(format!("more than one key in '{s}'")),
+                // letters and digits share their virtual key code with their uppercase ascii value
+                p if p.len() == 1 && p.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                    key = Some(p.to_ascii_uppercase().as_bytes()[0] as u32)
+                },
+                p => match p.strip_prefix('f').and_then(|n| n.parse::<u32>().ok()) {
+                    Some(n @ 1..=24) => key = Some(0x70 + n - 1),
+                    _ => return Err(format!("unknown key '{part}'")),
+                },
+            }
+        }
+
+        match (modifiers, key) {
+            (_, None) => Err(format!("no key in '{s}'")),
+            (0, Some(_)) => Err(format!("'{s}' needs at least one of ctrl, alt, shift or win")),
+            (modifiers, Some(key)) => Ok(Self { modifiers, key }),
+        }
+    }
+}
+
+/// Registers the hotkey on a dedicated thread running the message loop it gets delivered to,
+/// calling `on_press` whenever it's pressed
+#[cfg(windows)]
+pub fn spawn_hotkey_listener(
+    hotkey: Hotkey,
+    on_press: impl Fn() + Send + 'static,
+) -> anyhow::Result<()> {
+    use std::sync::mpsc;
+
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{RegisterHotKey, MOD_NOREPEAT};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetMessageW, MSG, WM_HOTKEY};
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        // SAFETY: registers for the current thread, WM_HOTKEY is posted to its message queue
+        let is_registered = unsafe {
+            RegisterHotKey(std::ptr::null_mut(), 1, hotkey.modifiers | MOD_NOREPEAT, hotkey.key)
+        };
+        let _ = tx.send(is_registered != 0);
+        if is_registered == 0 {
+            return;
+        }
+
+        let mut msg: MSG = unsafe { std::mem::zeroed() };
+        // SAFETY: msg is a valid MSG for GetMessageW to fill
+        while unsafe { GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) } > 0 {
+            if msg.message == WM_HOTKEY {
+                on_press();
+            }
+        }
+    });
+
+    match rx.recv()? {
+        true => Ok(()),
+        false => anyhow::bail!("Failed to register hotkey, it might be in use by another program"),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn spawn_hotkey_listener(
+    _hotkey: Hotkey,
+    _on_press: impl Fn() + Send + 'static,
+) -> anyhow::Result<()> {
+    anyhow::bail!("Global hotkeys are only supported on windows")
+}
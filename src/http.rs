@@ -0,0 +1,46 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use axum::extract::State;
+use axum::response::Html;
+use axum::routing::get;
+use axum::{Json, Router};
+use tokio::net::TcpListener;
+
+use crate::state::{self, SessionState};
+
+const DASHBOARD: &str = include_str!("../resources/dashboard.html");
+
+pub async fn spawn_status_server(
+    port: u16,
+    ws_port: Option<u16>,
+    state: Arc<Mutex<SessionState>>,
+) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("Failed to bind status api on port {port}"))?;
+    log::info!("Serving status api on http://127.0.0.1:{port}/status");
+    log::info!("Serving dashboard on http://127.0.0.1:{port}/");
+
+    let dashboard =
+        DASHBOARD.replace("{{WS_PORT}}", &ws_port.map_or("null".to_string(), |p| p.to_string()));
+
+    let app = Router::new()
+        .route("/", get(move || async move { Html(dashboard) }))
+        .route("/status", get(get_status))
+        .with_state(state);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            log::warn!("Status api stopped: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+/// No cors header, the dashboard is served from the same origin and other websites shouldn't be
+/// able to read the session
+async fn get_status(State(state): State<Arc<Mutex<SessionState>>>) -> Json<SessionState> {
+    Json(state::lock(&state).clone())
+}
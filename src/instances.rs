@@ -0,0 +1,125 @@
+use crate::events::GameEvent;
+use crate::models::{ClassInfo, MapChangeInfo};
+
+#[derive(Debug, Default)]
+struct Instance {
+    in_session: bool,
+    started_at: i64,
+    character: Option<ClassInfo>,
+    area: Option<MapChangeInfo>,
+    character_select_since: Option<i64>,
+    game_version: Option<String>,
+    server: Option<GameEvent>,
+}
+
+/// Merges the events of several game instances into a single session, only the instance driving
+/// the presence gets its events through
+#[derive(Debug)]
+pub struct InstanceRouter {
+    primary_character: Option<String>,
+    instances: Vec<Instance>,
+    driving: Option<usize>,
+}
+
+impl InstanceRouter {
+    pub fn new(count: usize, primary_character: Option<String>) -> Self {
+        Self {
+            primary_character,
+            instances: (0..count).map(|_| Instance::default()).collect(),
+            driving: None,
+        }
+    }
+
+    fn is_primary(&self, instance: &Instance) -> bool {
+        self.primary_character.as_ref().is_some_and(|name| {
+            instance.character.as_ref().is_some_and(|character| &character.username == name)
+        })
+    }
+
+    /// Instance playing the primary character, otherwise the one already driving or the first
+    /// one in a session
+    fn get_driving(&self) -> Option<usize> {
+        let in_session = || self.instances.iter().enumerate().filter(|(_, i)| i.in_session);
+
+        in_session()
+            .find(|(_, instance)| self.is_primary(instance))
+            .map(|(index, _)| index)
+            .or(self.driving.filter(|&index| self.instances[index].in_session))
+            .or_else(|| in_session().map(|(index, _)| index).next())
+    }
+
+    /// Events recreating the session of an instance that takes over the presence
+    fn get_session_snapshot(&self, index: usize) -> Vec<GameEvent> {
+        let instance = &self.instances[index];
+        let mut events = vec![GameEvent::SessionStarted {
+            ts: instance.started_at,
+            character: instance.character.clone(),
+        }];
+        events.extend(
+            instance.game_version.clone().map(|version| GameEvent::GameVersion { version }),
+        );
+        events.extend(instance.server.clone());
+        events.extend(instance.area.clone().map(GameEvent::AreaChanged));
+        events.extend(instance.character_select_since.map(|ts| GameEvent::CharacterSelect { ts }));
+        events
+    }
+
+    pub fn route(&mut self, index: usize, event: GameEvent) -> Vec<GameEvent> {
+        let instance = &mut self.instances[index];
+        match &event {
+            GameEvent::SessionStarted { ts, character } => {
+                *instance = Instance {
+                    in_session: true,
+                    started_at: *ts,
+                    character: character.clone(),
+                    area: None,
+                    character_select_since: None,
+                    game_version: None,
+                    server: None,
+                };
+            },
+            GameEvent::SessionEnded { .. } => instance.in_session = false,
+            GameEvent::LevelUp { character, .. } | GameEvent::CharacterUpdated(character) => {
+                instance.character = Some(character.clone())
+            },
+            GameEvent::AreaChanged(area) => {
+                instance.area = Some(area.clone());
+                instance.character_select_since = None;
+            },
+            GameEvent::CharacterSelect { ts } => {
+                instance.character = None;
+                instance.area = None;
+                instance.character_select_since.get_or_insert(*ts);
+            },
+            GameEvent::GameVersion { version } => instance.game_version = Some(version.clone()),
+            GameEvent::ServerConnected { gateway: Some(_), .. } => {
+                instance.server = Some(event.clone())
+            },
+            _ => {},
+        }
+
+        let driving = self.get_driving();
+        let previous = std::mem::replace(&mut self.driving, driving);
+        if driving == previous {
+            return match driving == Some(index) {
+                true => vec![event],
+                false => Vec::new(),
+            };
+        }
+
+        let mut events = Vec::new();
+        if previous.is_some() {
+            events.push(match event {
+                GameEvent::SessionEnded { .. } if previous == Some(index) => event,
+                _ => GameEvent::SessionEnded { ts: chrono::Utc::now().timestamp() },
+            });
+        }
+        if let Some(driving) = driving {
+            if self.instances.len() > 1 {
+                log::info!("Game instance {} is now driving the presence", driving + 1);
+            }
+            events.extend(self.get_session_snapshot(driving));
+        }
+        events
+    }
+}
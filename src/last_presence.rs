@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::task::JoinHandle;
+
+use crate::events::{EventBus, GameEvent};
+use crate::models::{ClassInfo, MapChangeInfo};
+use crate::supervisor;
+
+/// Last known character and area, restored on startup so the presence doesn't have to wait for the
+/// next level up
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LastPresence {
+    pub character: Option<ClassInfo>,
+    pub area: Option<MapChangeInfo>,
+    #[serde(default)]
+    pub act_progress: ActProgress,
+}
+
+/// Furthest act each character has reached, cruel acts come after the normal ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActProgress(HashMap<String, (bool, u8)>);
+
+impl ActProgress {
+    /// Records the character entering the area, returning the act if it's further than the
+    /// character has been before
+    pub fn reach(&mut self, character: &str, area_info: &MapChangeInfo) -> Option<(bool, u8)> {
+        let act = (area_info.is_cruel(), area_info.get_act()?);
+        let highest = self.0.entry(character.to_owned()).or_insert((false, 0));
+        match act > *highest {
+            true => {
+                *highest = act;
+                Some(act)
+            },
+            false => None,
+        }
+    }
+
+    pub fn is_known(&self, character: &str) -> bool {
+        self.0.contains_key(character)
+    }
+}
+
+impl LastPresence {
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(last_presence) => Some(last_presence),
+            Err(e) => {
+                log::warn!("Ignoring invalid last presence file {path:?}: {e}");
+                None
+            },
+        }
+    }
+}
+
+pub fn spawn_saver(
+    path: PathBuf,
+    mut last_presence: LastPresence,
+    bus: &EventBus,
+) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("last presence saver", async move || -> anyhow::Result<()> {
+        while let Some(event) = rx.recv().await {
+            match event {
+                GameEvent::SessionStarted { character: Some(class_info), .. }
+                | GameEvent::LevelUp { character: class_info, .. }
+                | GameEvent::CharacterUpdated(class_info) => {
+                    last_presence.character = Some(class_info)
+                },
+                GameEvent::AreaChanged(area_info) => {
+                    if let Some(character) = &last_presence.character {
+                        last_presence.act_progress.reach(&character.username, &area_info);
+                    }
+                    last_presence.area = Some(area_info)
+                },
+                GameEvent::CharacterSelect { .. } => {
+                    last_presence.character = None;
+                    last_presence.area = None
+                },
+                _ => continue,
+            }
+
+            if let Err(e) = fs::write(&path, serde_json::to_string(&last_presence)?).await {
+                log::warn!("Failed to save last presence to {path:?}: {e}");
+            }
+        }
+        Ok(())
+    }))
+}
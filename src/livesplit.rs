@@ -0,0 +1,52 @@
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+
+use crate::events::EventBus;
+use crate::splits::{SplitEvent, SplitTimer};
+use crate::supervisor;
+
+async fn send_commands(
+    stream: &mut Option<TcpStream>,
+    addr: &str,
+    commands: &[&str],
+) -> std::io::Result<()> {
+    let conn = match stream.take() {
+        Some(conn) => conn,
+        None => {
+            log::info!("Connecting to LiveSplit server at {addr}");
+            TcpStream::connect(addr).await?
+        },
+    };
+
+    let conn = stream.insert(conn);
+    for cmd in commands {
+        if let Err(e) = conn.write_all(format!("{cmd}\r\n").as_bytes()).await {
+            *stream = None;
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+pub fn spawn_livesplit_client(addr: String, bus: &EventBus) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("livesplit client", async move || -> anyhow::Result<()> {
+        let mut timer = SplitTimer::quiet();
+        let mut stream: Option<TcpStream> = None;
+
+        while let Some(event) = rx.recv().await {
+            let commands: &[&str] = match timer.on_event(&event) {
+                Some(SplitEvent::Started) => &["reset", "starttimer"],
+                Some(SplitEvent::Split | SplitEvent::Finished) => &["split"],
+                None => continue,
+            };
+
+            if let Err(e) = send_commands(&mut stream, &addr, commands).await {
+                log::warn!("Failed to send {commands:?} to LiveSplit server at {addr}: {e}");
+            }
+        }
+        Ok(())
+    }))
+}
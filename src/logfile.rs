@@ -0,0 +1,100 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub const LOG_FILE_NAME: &str = "poe2-drpc.log";
+
+/// Log file that moves itself to poe2-drpc.1.log, poe2-drpc.2.log, ... once it grows past the
+/// size cap, keeping a limited number of rotated files that aren't older than the max age
+pub struct RotatingLogFile {
+    dir: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    max_age: Duration,
+    file: File,
+    size: u64,
+    /// Records are written in several parts, only rotate between lines
+    is_line_start: bool,
+}
+
+impl RotatingLogFile {
+    pub fn open(
+        dir: &Path,
+        max_size: u64,
+        max_files: usize,
+        max_age: Duration,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let (file, size) = open_log(&dir.join(LOG_FILE_NAME))?;
+        let log_file = Self {
+            dir: dir.to_path_buf(),
+            max_size,
+            max_files,
+            max_age,
+            file,
+            size,
+            is_line_start: true,
+        };
+        log_file.remove_old_logs();
+        Ok(log_file)
+    }
+
+    fn get_rotated_path(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("poe2-drpc.{index}.log"))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let _ = fs::remove_file(self.get_rotated_path(self.max_files));
+        for index in (1..self.max_files).rev() {
+            let _ = fs::rename(self.get_rotated_path(index), self.get_rotated_path(index + 1));
+        }
+
+        let path = self.dir.join(LOG_FILE_NAME);
+        match self.max_files {
+            0 => fs::remove_file(&path)?,
+            _ => fs::rename(&path, self.get_rotated_path(1))?,
+        }
+        (self.file, self.size) = open_log(&path)?;
+        self.remove_old_logs();
+        Ok(())
+    }
+
+    /// Removes rotated logs past the max age, the current log is never touched
+    fn remove_old_logs(&self) {
+        for index in 1..=self.max_files {
+            let path = self.get_rotated_path(index);
+            let is_expired = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age > self.max_age);
+            if is_expired {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+fn open_log(path: &Path) -> io::Result<(File, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+impl Write for RotatingLogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.is_line_start && self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        self.is_line_start = buf[..written].ends_with(b"\n");
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
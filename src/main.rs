@@ -1,96 +1,629 @@
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
-use std::path::PathBuf;
-use std::{fs, thread};
-
-use clap::Parser;
-use discord_rich_presence::activity::{Activity, Assets, Timestamps};
-use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
-use lazy_static::lazy_static;
-use models::{ClassInfo, MapChangeInfo, Translations};
-use regex::Regex;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{IsTerminal, SeekFrom};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use area_assets::AreaAssets;
+use character_api::{CharacterApi, Poesessid};
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use control::Control;
+use discord_rich_presence::DiscordIpcClient;
+use events::{EventBus, GameEvent};
+use export::ExportFormat;
+use fern::colors::{Color, ColoredLevelConfig};
+use game::Game;
+use history::History;
+use hotkey::Hotkey;
+use instances::InstanceRouter;
+use last_presence::LastPresence;
+use logfile::RotatingLogFile;
+use milestones::MilestoneAction;
+use models::{ClassInfo, MapChangeInfo, StringOverride, Translations};
+use obs::ObsFile;
+use paths::DataPaths;
+use patterns::{get_line_timestamp, get_log_opened_at, LineMatch, LogPatterns, PatternOverride};
+use presence::{Presence, PresenceOptions, ProfileLink};
+use regex::Captures;
+use reminders::PlaytimeReminder;
+use state::SessionState;
 use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, BufReader};
+use webhook::Webhook;
 
+mod area_assets;
+mod audit;
+mod autostart;
+mod background;
+mod character_api;
+mod config;
+mod control;
+mod crash;
+mod discord;
+mod events;
+mod export;
+mod game;
+mod gateway;
+#[cfg(feature = "gui")]
+mod gui;
+mod history;
+mod hotkey;
+mod http;
+mod instances;
+mod last_presence;
+mod livesplit;
+mod logfile;
+mod milestones;
 mod models;
+mod notify;
+mod obs;
+mod paths;
+mod patterns;
+mod presence;
+mod reminders;
+mod remote;
+mod setup;
+mod splits;
+mod state;
+mod stats;
+mod summary;
+mod supervisor;
+mod update;
+mod webhook;
+mod ws;
 
-const DEFAULT_TRANSLATIONS: &str = include_str!("../resources/translations_en.json");
-const DEFAULT_DIRECTORIES: [&str; 2] = [
-    "C:\\Program Files (x86)\\Grinding Gear Games\\Path of Exile 2",
-    "C:\\Program Files (x86)\\Steam\\steamapps\\common\\Path of Exile 2",
-];
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print playtime, most visited zones and levelling pace from the recorded history
+    Stats {
+        /// Number of days to report daily and weekly playtime for
+        #[arg(long, default_value_t = 14)]
+        days: u32,
+    },
+    /// Start the tool with the given flags when logging into windows
+    InstallAutostart,
+    /// Stop starting the tool when logging into windows
+    UninstallAutostart,
+    /// Download the latest release and replace the running binary with it
+    SelfUpdate,
+    /// Check that the class, ascendency and area asset images are uploaded to the discord
+    /// application, listing the missing ones
+    AuditAssets,
+    /// Parse a saved game log and report the events found and how fast it was parsed, to check
+    /// pattern overrides against a real log
+    CheckLog {
+        /// Game log to parse, e.g. a copy of Client.txt
+        path: PathBuf,
+    },
+    /// Open a settings window to pick the flags, preview the presence and start the tool with them
+    #[cfg(feature = "gui")]
+    Settings,
+    /// Tail the game log and stream its events to a presenter on another machine
+    Agent {
+        /// Address to accept presenter connections on, e.g. 0.0.0.0:7822 to accept them from other
+        /// machines, which requires a --token
+        #[arg(long, default_value = "127.0.0.1:7822")]
+        listen: SocketAddr,
 
-const PROCESS_NAMES: [&str; 4] =
-    ["PathOfExile_x64Steam.exe", "PathOfExile_x64.exe", "PathOfExileSteam.exe", "PathOfExile.exe"];
+        /// Shared secret presenters have to send to receive the events
+        #[arg(long, env = "POE2_RPC_AGENT_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+    },
+    /// Show the presence for an agent running on another machine
+    Presenter {
+        /// Websocket address of the agent, e.g. ws://192.168.1.10:7822
+        agent: String,
 
-lazy_static! {
-    static ref RGX_GENERATING_AREA: Regex = Regex::new(r#"] Generating level (\d+) area "([^"]+)" with seed (\d+)"#).unwrap();
-    static ref RGX_JOINED_AREA: Regex = Regex::new(r#": (\w+) has joined the area."#).unwrap();
-    static ref RGX_LEVEL_UP: Regex = Regex::new(r#": (\w+) \((\w+)\) is now level (\d+)"#).unwrap();
+        /// Shared secret the agent was started with
+        #[arg(long, env = "POE2_RPC_AGENT_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+    },
 }
 
 #[derive(Parser, Debug)]
-#[clap(about, author, version)]
+#[clap(about, author, version, args_override_self = true)]
 struct Opt {
-    /// Path to the game directory
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Game to show the presence for
+    #[arg(long, value_enum, default_value_t = Game::Poe2)]
+    game: Game,
+
+    /// Path to the game directory, repeat it to follow several game instances at once
     #[arg(short, long)]
-    game_dir: Option<PathBuf>,
+    game_dir: Vec<PathBuf>,
+
+    /// Character whose game instance drives the presence when following several instances,
+    /// otherwise the first instance in a session does
+    #[arg(long)]
+    primary_character: Option<String>,
+
+    /// Path to the game log, for installs that keep it somewhere other than logs/Client.txt in the
+    /// game directory
+    #[arg(short, long, conflicts_with = "game_dir")]
+    log_file: Option<PathBuf>,
 
     /// Path to translations.json
     #[arg(short, long)]
     translations_file: Option<PathBuf>,
+
+    /// Replace a text fragment of the presence (e.g. character_select=Picking a character), taking
+    /// precedence over the translations file, can be repeated
+    #[arg(long = "presence-string", value_name = "NAME=TEMPLATE")]
+    presence_strings: Vec<StringOverride>,
+
+    /// Id of the discord application to show the presence as, for using your own uploaded art
+    #[arg(long, default_value = "550890770056347648")]
+    discord_client_id: String,
+
+    /// Path to a json list of area code patterns and the discord assets to show for them, e.g.
+    /// [{"pattern": "^Hideout", "image": "my_hideout", "text": "Chilling"}]
+    #[arg(long)]
+    area_assets: Option<PathBuf>,
+
+    /// Account name (e.g. Name#1234) of the played characters, used for the character api and the
+    /// profile button
+    #[arg(long)]
+    account_name: Option<String>,
+
+    /// POESESSID cookie of a logged in pathofexile.com session, enables fetching the class, level
+    /// and league of the played character from the official character api
+    #[arg(long, requires = "account_name", env = "POESESSID", hide_env_values = true)]
+    poesessid: Option<Poesessid>,
+
+    /// Port to serve the local http status api on (e.g. 7820), disabled if not set
+    #[arg(long)]
+    http_port: Option<u16>,
+
+    /// Port to broadcast game events over websocket on (e.g. 7821), disabled if not set
+    #[arg(long)]
+    ws_port: Option<u16>,
+
+    /// Continuously write the current character, class, area, level or presence into a text file
+    /// (e.g. area=C:\obs\area.txt), can be passed multiple times
+    #[arg(long, value_name = "FIELD=PATH")]
+    obs_file: Vec<ObsFile>,
+
+    /// Discord webhook url to post level ups, deaths and new acts to
+    #[arg(long)]
+    webhook_url: Option<String>,
+
+    /// Minimum character level for level ups to be posted to the webhook
+    #[arg(long, default_value_t = 1)]
+    webhook_min_level: u16,
+
+    /// Character levels that trigger the milestone actions, ascending always does
+    #[arg(long, value_delimiter = ',', default_value = "90,95,100")]
+    milestone_levels: Vec<u16>,
+
+    /// What to do when the character reaches a milestone level or ascends, can be repeated,
+    /// disabled if not set
+    #[arg(long, value_enum)]
+    milestone_action: Vec<MilestoneAction>,
+
+    /// Remind to take a break after playing this long without one, for every day (4h) or some
+    /// weekdays (sat,sun=6h or mon-fri=3h), can be repeated
+    #[arg(long, value_name = "[DAYS=]DURATION")]
+    playtime_reminder: Vec<PlaytimeReminder>,
+
+    /// How to deliver playtime reminders, can be repeated
+    #[arg(long, value_enum, default_value = "toast")]
+    playtime_reminder_action: Vec<MilestoneAction>,
+
+    /// Show a windows toast notification for incoming whispers
+    #[arg(long)]
+    whisper_toasts: bool,
+
+    /// Minimum time between two whisper toasts
+    #[arg(long, default_value = "5s")]
+    whisper_toast_cooldown: humantime::Duration,
+
+    /// Show "Trading in hideout" while receiving trade whispers in your hideout
+    #[arg(long)]
+    trade_state: bool,
+
+    /// Number of trade whispers within the quiet period that switch to the trading state
+    #[arg(long, default_value_t = 2)]
+    trade_burst: usize,
+
+    /// Time without trade whispers before switching back from the trading state
+    #[arg(long, default_value = "2m")]
+    trade_quiet_period: humantime::Duration,
+
+    /// Show the number of distinct trade whispers received this session in the small text
+    #[arg(long)]
+    whisper_counter: bool,
+
+    /// Show the number of endgame maps completed this session in the small text
+    #[arg(long)]
+    map_counter: bool,
+
+    /// Show the game version parsed from the log in the small text
+    #[arg(long)]
+    show_game_version: bool,
+
+    /// Show the login gateway (e.g. Frankfurt) parsed from the log in the small text
+    #[arg(long)]
+    show_gateway: bool,
+
+    /// Show the latency of the last server connection (e.g. "ping ~45 ms") in the small text
+    #[arg(long)]
+    show_ping: bool,
+
+    /// Show the seed of the current instance in the small text
+    #[arg(long)]
+    show_seed: bool,
+
+    /// Show the party size while other players are in the same instance
+    #[arg(long)]
+    party_hint: bool,
+
+    /// Round the displayed level down to a bracket of this size (e.g. 5 shows "90+" at level 93)
+    #[arg(long)]
+    level_bracket: Option<u16>,
+
+    /// Add a button linking to the played character's profile or poe.ninja build page
+    #[arg(long, value_enum, requires = "account_name")]
+    profile_button: Option<ProfileLink>,
+
+    /// Time campaign splits (act transitions and act bosses) from a fresh character in the Riverbank,
+    /// showing the current split and run time in the presence. Path of Exile 2 only.
+    #[arg(long)]
+    splits: bool,
+
+    /// Address of a LiveSplit server to start, split and reset the timer on campaign splits. Path of
+    /// Exile 2 only.
+    #[arg(long, value_name = "HOST:PORT")]
+    livesplit: Option<String>,
+
+    /// Global hotkey that hides the discord activity until it's pressed again, e.g. Ctrl+Alt+P
+    #[arg(long)]
+    toggle_hotkey: Option<Hotkey>,
+
+    /// Accept pause, resume, status and set-note commands from other programs over a named pipe
+    /// (\\.\pipe\poe2-drpc) or unix socket
+    #[arg(long)]
+    control: bool,
+
+    /// Exit once the game closes instead of waiting for it to start again
+    #[arg(long)]
+    exit_with_game: bool,
+
+    /// Keep running in the background without a console window, logging only to poe2-drpc.log
+    #[arg(long)]
+    background: bool,
+
+    /// Don't check for a newer release on startup
+    #[arg(long)]
+    no_update_check: bool,
+
+    /// Replace a built-in log pattern (e.g. level_up=REGEX) when the game changes its log format,
+    /// can be repeated
+    #[arg(long = "pattern", value_name = "NAME=REGEX")]
+    patterns: Vec<PatternOverride>,
+
+    /// Additional game executable to look for (e.g. a renamed or regional build), can be repeated
+    #[arg(long = "process-name", value_name = "NAME")]
+    process_names: Vec<String>,
+
+    /// How often to check whether the game is running while it's closed
+    #[arg(long, default_value = "5s")]
+    process_check_interval: humantime::Duration,
+
+    /// How often to poll the log file for new lines
+    #[arg(long, default_value = "500ms")]
+    log_poll_interval: humantime::Duration,
+
+    /// Minimum time between discord activity updates, discord rate limits frequent updates
+    #[arg(long, default_value = "4s")]
+    min_activity_interval: humantime::Duration,
+
+    /// Show more detail on the console, -vv for everything
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Only show warnings on the console, -qq for errors only
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    quiet: u8,
+
+    /// Config file to read flags from instead of poe2-drpc.cfg in the config directory
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Keep the config, state, history and log files next to the executable instead of the app
+    /// data directory, implied by a poe2-drpc.cfg next to the executable
+    #[arg(long, global = true)]
+    portable: bool,
+
+    /// Directory to write the tool's own poe2-drpc.log and its rotated copies to, the data
+    /// directory by default
+    #[arg(long)]
+    log_dir: Option<PathBuf>,
+
+    /// Size in megabytes after which poe2-drpc.log is rotated
+    #[arg(long, default_value_t = 10)]
+    log_max_size_mb: u64,
+
+    /// Number of rotated poe2-drpc.log files to keep
+    #[arg(long, default_value_t = 5)]
+    log_max_files: usize,
+
+    /// Age after which rotated poe2-drpc.log files are removed
+    #[arg(long, default_value = "30d")]
+    log_max_age: humantime::Duration,
+
+    /// Path to the sqlite database sessions, area visits, level ups and deaths are recorded to,
+    /// poe2-drpc.db in the data directory by default
+    #[arg(long, global = true)]
+    history_db: Option<PathBuf>,
+
+    /// Don't record any history
+    #[arg(long)]
+    no_history: bool,
+
+    /// Path to the file the last known character and area are saved to, restored on startup,
+    /// poe2-drpc-state.json in the data directory by default
+    #[arg(long)]
+    state_file: Option<PathBuf>,
+
+    /// Don't save or restore the last known character and area
+    #[arg(long)]
+    no_state_file: bool,
+
+    /// Directory to export a summary of every session to when the game closes
+    #[arg(long)]
+    export_dir: Option<PathBuf>,
+
+    /// Format of the exported session summaries
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+    export_format: ExportFormat,
 }
 
-fn is_poe_running(sys: &mut System) -> bool {
+/// Checks for a running game, limited to the executables inside the game directory if given
+fn is_poe_running(sys: &mut System, log: &GameLog, extra_process_names: &[String]) -> bool {
+    let process_names = log.game.get_process_names();
+    let game_dir = log.game_dir.as_deref();
+    let is_game_process = |name: &str| {
+        process_names
+            .iter()
+            .copied()
+            .chain(extra_process_names.iter().map(String::as_str))
+            .any(|n| n.eq_ignore_ascii_case(name))
+    };
+
     sys.refresh_processes(ProcessesToUpdate::All, true);
-    sys.processes_by_name("PathOfExile".as_ref())
-        .any(|p| p.name().to_str().is_some_and(|n| PROCESS_NAMES.contains(&n)))
+    sys.processes()
+        .values()
+        .filter(|p| p.name().to_str().is_some_and(is_game_process))
+        .any(|p| game_dir.is_none_or(|dir| p.exe().is_some_and(|exe| exe.starts_with(dir))))
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    fern::Dispatch::new()
-        .format(|out, message, record| {
-            out.finish(format_args!(
-                "[{} {} {}] {}",
-                humantime::format_rfc3339(std::time::SystemTime::now()),
-                record.level(),
-                record.target(),
-                message
-            ))
-        })
-        .chain(fern::Dispatch::new().level(log::LevelFilter::Info).chain(std::io::stdout()))
-        .chain(
-            fern::Dispatch::new()
-                .level(log::LevelFilter::Trace)
-                .chain(fern::log_file("poe2-drpc.log")?),
-        )
-        .apply()?;
-
-    let Opt { game_dir, translations_file } = Opt::parse();
-    log::trace!("Args: {{ game_dir: {game_dir:?}, translations_file: {translations_file:?} }}");
-
-    let translations: Translations = serde_json::from_str(
-        &translations_file
-            .map(|f| fs::read_to_string(f).unwrap())
-            .unwrap_or(DEFAULT_TRANSLATIONS.to_string()),
-    )?;
-    log::trace!("Translations: {translations:#?}");
+/// Event of a log line, lines whose pattern has a group that didn't participate in the match are
+/// skipped since overrides may make groups optional
+fn parse_log_line(
+    line: &LineMatch,
+    patterns: &LogPatterns,
+    translations: &Translations,
+    user_blacklist: &[String],
+) -> Option<GameEvent> {
+    let ts = get_line_timestamp(line.line()).unwrap_or_else(|| chrono::Utc::now().timestamp());
 
-    let game_dir = game_dir
-        .or_else(|| {
-            DEFAULT_DIRECTORIES
-                .iter()
-                .find(|&d| fs::metadata(d).is_ok())
-                .map(|d| d.to_string())
-                .map(PathBuf::from)
+    if let Some(caps) = line.captures("level_up") {
+        ClassInfo::parse_from_capture(&caps, translations, user_blacklist)
+            .map(|character| GameEvent::LevelUp { character, ts })
+    } else if let Some(caps) = line.captures("generating_area") {
+        MapChangeInfo::parse_from_captures(&caps, translations, ts).map(GameEvent::AreaChanged)
+    } else if let Some(caps) = line.captures("joined_area") {
+        Some(GameEvent::PlayerJoined { username: caps.get(1)?.as_str().to_string() })
+    } else if let Some(caps) = line.captures("whisper") {
+        let message = caps.get(2)?.as_str().trim().to_string();
+        Some(GameEvent::Whisper {
+            from: caps.get(1)?.as_str().to_string(),
+            is_trade: patterns.trade_whisper.is_match(&message),
+            message,
         })
-        .ok_or("Game directory not found")?;
-    log::trace!("Game directory: {game_dir:?}");
+    } else if let Some(caps) = line.captures("left_area") {
+        Some(GameEvent::PlayerLeft { username: caps.get(1)?.as_str().to_string() })
+    } else if let Some(caps) = line.captures("game_version") {
+        Some(GameEvent::GameVersion { version: caps.get(1)?.as_str().to_string() })
+    } else if let Some(caps) = line.captures("server_connect") {
+        get_server_event(&caps)
+    } else {
+        let username = line.captures("slain")?.get(1)?.as_str();
+        match user_blacklist.iter().any(|u| u == username) {
+            true => None,
+            false => Some(GameEvent::Death { username: username.to_string(), ts }),
+        }
+    }
+}
 
-    let log_file = game_dir.join("logs/Client.txt");
-    let log_file = fs::File::open(log_file)?;
-    log::trace!("Opened log file");
+/// Where the player was when the log ends, the last area or the character select screen
+fn get_resumed_event(
+    log: &str,
+    patterns: &LogPatterns,
+    translations: &Translations,
+) -> Option<GameEvent> {
+    let line = log.lines().rev().find(|line| {
+        patterns.generating_area.is_match(line) || patterns.character_select.is_match(line)
+    })?;
+    let ts = get_line_timestamp(line).unwrap_or_else(|| chrono::Utc::now().timestamp());
 
-    let mut rpc = DiscordIpcClient::new("550890770056347648")?;
-    log::info!("Created discord ipc client");
+    match patterns.generating_area.captures(line) {
+        Some(caps) => {
+            MapChangeInfo::parse_from_captures(&caps, translations, ts).map(GameEvent::AreaChanged)
+        },
+        None => Some(GameEvent::CharacterSelect { ts }),
+    }
+}
+
+fn get_server_event(caps: &Captures) -> Option<GameEvent> {
+    let address = caps.get(1)?.as_str();
+    Some(GameEvent::ServerConnected {
+        address: address.to_string(),
+        gateway: gateway::get_gateway_name(address).map(str::to_string),
+        latency_ms: caps.get(2).and_then(|ms| ms.as_str().parse().ok()),
+    })
+}
+
+fn load_translations(
+    game: Game,
+    translations_file: Option<&Path>,
+    string_overrides: &[StringOverride],
+) -> anyhow::Result<Translations> {
+    let mut translations: Translations = match translations_file {
+        Some(f) => serde_json::from_str(&fs::read_to_string(f)?)?,
+        None => serde_json::from_str(game.get_default_translations())?,
+    };
+    translations.strings.apply_overrides(string_overrides);
+    Ok(translations)
+}
+
+/// Times matching every line of the log, trying the patterns one after another and in a single
+/// pass, returning the number of matched lines of each
+fn bench_line_matching(
+    log: &str,
+    patterns: &LogPatterns,
+) -> ((usize, Duration), (usize, Duration)) {
+    let started = Instant::now();
+    let sequential =
+        log.lines().filter(|line| patterns.captures_sequential(line).is_some()).count();
+    let sequential_elapsed = started.elapsed();
+
+    let started = Instant::now();
+    let single_pass =
+        log.lines().filter(|line| patterns.match_line(line).first_captures().is_some()).count();
+    let single_pass_elapsed = started.elapsed();
+
+    ((sequential, sequential_elapsed), (single_pass, single_pass_elapsed))
+}
+
+/// Runs a whole log through the line patterns, counting the events and comparing how fast the
+/// lines are matched one pattern at a time and in a single pass
+fn check_log(
+    path: &Path,
+    patterns: &LogPatterns,
+    translations: &Translations,
+) -> anyhow::Result<()> {
+    let log = String::from_utf8_lossy(&fs::read(path)?).into_owned();
+
+    let mut user_blacklist = Vec::new();
+    let mut counts = BTreeMap::<String, usize>::new();
+    for log_line in log.lines() {
+        let line = patterns.match_line(log_line);
+        if line.is_empty() {
+            continue;
+        }
+        if line.is_match("character_select") {
+            *counts.entry("character_select".to_string()).or_default() += 1;
+        }
+        let Some(event) = parse_log_line(&line, patterns, translations, &user_blacklist) else {
+            continue;
+        };
+        if let GameEvent::PlayerJoined { username } = &event {
+            user_blacklist.push(username.clone());
+        }
+        let name = serde_json::to_value(&event)?["type"].as_str().unwrap_or_default().to_string();
+        *counts.entry(name).or_default() += 1;
+    }
+
+    for (name, count) in &counts {
+        println!("{name:>20}: {count}");
+    }
+
+    let lines = log.lines().count();
+    let ((sequential, sequential_elapsed), (single_pass, single_pass_elapsed)) =
+        bench_line_matching(&log, patterns);
+    let lines_per_sec = |elapsed: Duration| lines as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "Matched {sequential} of {lines} lines one pattern at a time in {sequential_elapsed:.2?} \
+         ({:.0} lines/s)",
+        lines_per_sec(sequential_elapsed)
+    );
+    println!(
+        "Matched {single_pass} of {lines} lines in a single pass in {single_pass_elapsed:.2?} \
+         ({:.0} lines/s, {:.1}x)",
+        lines_per_sec(single_pass_elapsed),
+        sequential_elapsed.as_secs_f64() / single_pass_elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+    if sequential != single_pass {
+        log::warn!("The single pass matched different lines than trying the patterns in turn");
+    }
+    Ok(())
+}
+
+/// Log of a game instance, along with the directory the instance runs from when following several
+struct GameLog {
+    game: Game,
+    path: PathBuf,
+    game_dir: Option<PathBuf>,
+}
+
+/// Logs to tail, any running game counts when there is only a single instance
+fn get_game_logs(
+    game: Game,
+    log_file: Option<PathBuf>,
+    game_dirs: Vec<PathBuf>,
+) -> Result<Vec<GameLog>, Box<dyn std::error::Error>> {
+    Ok(match log_file {
+        Some(log_file) => vec![GameLog { game, path: log_file, game_dir: None }],
+        None if game_dirs.len() > 1 => game_dirs
+            .into_iter()
+            .map(|game_dir| GameLog {
+                game,
+                path: game_dir.join("logs/Client.txt"),
+                game_dir: Some(game_dir),
+            })
+            .collect(),
+        None => {
+            let game_dir = game_dirs
+                .into_iter()
+                .next()
+                .or_else(|| {
+                    game.get_default_directories()
+                        .iter()
+                        .find(|&d| fs::metadata(d).is_ok())
+                        .map(|d| d.to_string())
+                        .map(PathBuf::from)
+                })
+                .ok_or("Game directory not found")?;
+            log::trace!("Game directory: {game_dir:?}");
+            vec![GameLog { game, path: game_dir.join("logs/Client.txt"), game_dir: None }]
+        },
+    })
+}
+
+/// How the game logs are followed, shared by all followed instances
+#[derive(Clone, Copy)]
+struct TailOptions<'a> {
+    extra_process_names: &'a [String],
+    process_check_interval: Duration,
+    log_poll_interval: Duration,
+    exit_with_game: bool,
+    last_presence: Option<&'a LastPresence>,
+}
+
+/// Tails the game log while the game is running, emitting the parsed events
+async fn tail_game_log(
+    log: &GameLog,
+    options: TailOptions<'_>,
+    patterns: &LogPatterns,
+    translations: &Translations,
+    emit: impl Fn(GameEvent),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let TailOptions {
+        extra_process_names,
+        process_check_interval,
+        log_poll_interval,
+        exit_with_game,
+        last_presence,
+    } = options;
+    let log_file = tokio::fs::File::open(&log.path).await?;
+    log::trace!("Opened log file {:?}", log.path);
 
     let mut sys = System::new_with_specifics(
         RefreshKind::nothing().with_processes(ProcessRefreshKind::everything()),
@@ -98,105 +631,572 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     log::info!("Created sysinfo");
 
     let mut log_bufr = BufReader::new(log_file);
-
-    let mut activity = Activity::new();
-    let mut last_area: Option<MapChangeInfo> = None;
-    let mut last_class: Option<ClassInfo> = None;
     let mut user_blacklist: Vec<String> = Vec::new();
+    let mut last_area: Option<MapChangeInfo> = None;
+    let mut restored_area = last_presence.and_then(|p| p.area.clone());
+    let mut is_resuming = true;
 
     log::info!("Starting main loop");
-    loop {
-        if !is_poe_running(&mut sys) {
-            thread::sleep(std::time::Duration::from_secs(5));
-            continue;
+    let mut in_session = false;
+    supervisor::run("main loop", async || -> Result<(), Box<dyn std::error::Error>> {
+        if in_session {
+            // the previous run failed mid-session, end it before starting a new one
+            emit(GameEvent::SessionEnded { ts: chrono::Utc::now().timestamp() });
+            in_session = false;
         }
 
-        rpc.connect()?;
-        log::trace!("Connected to discord rpc");
+        loop {
+            if !is_poe_running(&mut sys, log, extra_process_names) {
+                // the last area is only still current if the game was already running on startup
+                is_resuming = false;
+                tokio::time::sleep(process_check_interval).await;
+                continue;
+            }
+
+            // the log isn't guaranteed to be valid utf-8, don't let a single line take the session down
+            let mut log_bytes = Vec::new();
+            log_bufr.read_to_end(&mut log_bytes).await?;
+            let log_str = String::from_utf8_lossy(&log_bytes);
+
+            patterns.joined_area.captures_iter(&log_str).for_each(|caps| {
+                if let Some(username) = caps.get(1) {
+                    user_blacklist.push(username.as_str().to_owned());
+                }
+            });
+            log::trace!("Initial user blacklist: {user_blacklist:#?}");
 
-        let mut log_str = String::new();
-        log_bufr.read_to_string(&mut log_str)?;
+            let character = patterns
+                .level_up
+                .captures_iter(&log_str)
+                .filter_map(|caps| {
+                    ClassInfo::parse_from_capture(&caps, translations, &user_blacklist)
+                })
+                .last()
+                .or_else(|| last_presence.and_then(|p| p.character.clone()));
+            log::trace!("Initial class info: {character:#?}");
 
-        RGX_JOINED_AREA.captures_iter(&log_str).for_each(|caps| {
-            if let Some(username) = caps.get(1) {
-                user_blacklist.push(username.as_str().to_owned());
+            let version = patterns
+                .game_version
+                .captures_iter(&log_str)
+                .filter_map(|caps| caps.get(1).map(|m| m.as_str().to_string()))
+                .last();
+            let server = patterns
+                .server_connect
+                .captures_iter(&log_str)
+                .filter_map(|caps| get_server_event(&caps))
+                .filter(|event| {
+                    matches!(event, GameEvent::ServerConnected { gateway: Some(_), .. })
+                })
+                .last();
+
+            // a game found running on startup was launched before, otherwise it just was
+            let started_at = is_resuming
+                .then(|| get_log_opened_at(&log_str))
+                .flatten()
+                .unwrap_or_else(|| chrono::Utc::now().timestamp());
+            emit(GameEvent::SessionStarted { ts: started_at, character });
+            if let Some(version) = version {
+                emit(GameEvent::GameVersion { version });
             }
-        });
-        log::trace!("Initial user blacklist: {user_blacklist:#?}");
-
-        if let Some(last_class_info) = RGX_LEVEL_UP
-            .captures_iter(&log_str)
-            .filter_map(|caps| ClassInfo::parse_from_capture(&caps, &user_blacklist))
-            .last()
-        {
-            last_class = Some(last_class_info);
-        }
-        log::trace!("Initial class info: {last_class:#?}");
-
-        log_bufr.seek(SeekFrom::End(0))?;
-
-        while is_poe_running(&mut sys) {
-            let mut log_line = String::new();
-
-            if log_bufr.read_line(&mut log_line)? == 0 {
-                if last_class.is_some() || last_area.is_some() {
-                    log::info!(
-                        "Updating activity {{ class: {last_class:#?}, instance: {last_area:#?} }}"
-                    );
-
-                    if let Some(mut class_info) = last_class.take() {
-                        activity = activity.details(class_info.username);
-
-                        let mut assets = Assets::default();
-                        if let Some(ascd) = class_info.ascendency.take() {
-                            assets = assets
-                                .large_image(ascd.get_discord_image_name())
-                                .large_text(format!("{ascd} ({})", class_info.level))
-                                .small_image(class_info.class.get_discord_image_name())
-                                .small_text(class_info.class);
-                        } else {
-                            assets = assets
-                                .large_image(class_info.class.get_discord_image_name())
-                                .large_text(format!("{} ({})", class_info.class, class_info.level));
-                        }
-
-                        activity = activity.assets(assets);
+            if let Some(server) = server {
+                emit(server);
+            }
+            if std::mem::take(&mut is_resuming) {
+                // the saved area only stands in when the log has been cleared since
+                let event = get_resumed_event(&log_str, patterns, translations)
+                    .or_else(|| restored_area.take().map(GameEvent::AreaChanged));
+                if let Some(event) = event {
+                    if let GameEvent::AreaChanged(area_info) = &event {
+                        last_area = Some(area_info.clone());
                     }
+                    emit(event);
+                }
+            }
+            in_session = true;
 
-                    if let Some(instance_info) = last_area.take() {
-                        activity = activity
-                            .state(format!("{} ({})", &instance_info.name, instance_info.level))
-                            .timestamps(Timestamps::default().start(instance_info.ts));
+            log_bufr.seek(SeekFrom::End(0)).await?;
+
+            // the game only needs checking on once the log goes quiet, not for every line of a burst
+            let mut line_buf = Vec::new();
+            loop {
+                line_buf.clear();
+                if log_bufr.read_until(b'\n', &mut line_buf).await? == 0 {
+                    if !is_poe_running(&mut sys, log, extra_process_names) {
+                        break;
                     }
+                    tokio::time::sleep(log_poll_interval).await;
+                    continue;
+                }
 
-                    rpc.set_activity(activity.clone())?;
+                let log_line = String::from_utf8_lossy(&line_buf);
+                let line = patterns.match_line(&log_line);
+                if line.is_empty() {
+                    continue;
+                }
+                crash::record_line(&log_line);
+                // the client connects back to the login gateway to show the character list
+                if line.is_match("character_select") {
+                    let ts = get_line_timestamp(&log_line)
+                        .unwrap_or_else(|| chrono::Utc::now().timestamp());
+                    emit(GameEvent::CharacterSelect { ts });
+                }
+                if let Some(mut event) =
+                    parse_log_line(&line, patterns, translations, &user_blacklist)
+                {
+                    match &mut event {
+                        GameEvent::PlayerJoined { username }
+                            if !user_blacklist.contains(username) =>
+                        {
+                            user_blacklist.push(username.clone());
+                        },
+                        // keep the timer running when re-entering the instance we just left
+                        GameEvent::AreaChanged(area_info) => {
+                            if let Some(last) =
+                                last_area.as_ref().filter(|last| last.is_same_instance(area_info))
+                            {
+                                area_info.ts = last.ts;
+                            }
+                            last_area = Some(area_info.clone());
+                        },
+                        _ => {},
+                    }
+                    emit(event);
                 }
-                thread::sleep(std::time::Duration::from_millis(500));
-                continue;
             }
 
-            if let Some(class_info) = RGX_LEVEL_UP
-                .captures(&log_line)
-                .and_then(|caps| ClassInfo::parse_from_capture(&caps, &user_blacklist))
-            {
-                last_class = Some(class_info);
-            } else if let Some(area_info) = RGX_GENERATING_AREA
-                .captures(&log_line)
-                .map(|caps| MapChangeInfo::parse_from_captures(&caps, &translations))
-            {
-                last_area = Some(area_info);
-            } else if let Some(caps) = RGX_JOINED_AREA.captures(&log_line) {
-                let username = caps[1].to_string();
-                if !user_blacklist.contains(&username) {
-                    user_blacklist.push(username);
-                }
+            // the game doesn't log closing, the end is when it was found gone
+            emit(GameEvent::SessionEnded { ts: chrono::Utc::now().timestamp() });
+            in_session = false;
+            last_area = None;
+
+            if exit_with_game {
+                return Ok(());
+            }
+        }
+    })
+    .await;
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli_args: Vec<OsString> = std::env::args_os().collect();
+    let paths = DataPaths::from_args(&cli_args)?;
+    let migrated = paths.migrate_legacy_files();
+    // ask for the basics when started without any flags for the first time, e.g. by double clicking
+    if cli_args.len() == 1 && !paths.config_file.exists() && std::io::stdin().is_terminal() {
+        setup::run_setup(&paths.config_file)?;
+    }
+    let config_args = config::load_args(&paths.config_file)?;
+    let config_args = config::without_overridden(&Opt::command(), config_args, &cli_args)
+        .into_iter()
+        .map(OsString::from);
+    let opt = Opt::parse_from(
+        cli_args[..1].iter().cloned().chain(config_args).chain(cli_args[1..].iter().cloned()),
+    );
+    let is_detached = opt.background && background::is_detached();
+    fs::create_dir_all(&paths.data_dir)?;
+    let log_dir = opt.log_dir.clone().unwrap_or_else(|| paths.data_dir.clone());
+
+    let log_file: Box<dyn std::io::Write + Send> = Box::new(RotatingLogFile::open(
+        &log_dir,
+        opt.log_max_size_mb * 1024 * 1024,
+        opt.log_max_files,
+        opt.log_max_age.into(),
+    )?);
+
+    let mut dispatch = fern::Dispatch::new().chain(
+        fern::Dispatch::new()
+            .format(|out, message, record| {
+                out.finish(format_args!(
+                    "[{} {} {}] {}",
+                    humantime::format_rfc3339(std::time::SystemTime::now()),
+                    record.level(),
+                    record.target(),
+                    message
+                ))
+            })
+            .level(log::LevelFilter::Trace)
+            .chain(log_file),
+    );
+    if !is_detached {
+        let console_level = match (opt.verbose, opt.quiet) {
+            (0, 0) => log::LevelFilter::Info,
+            (1, _) => log::LevelFilter::Debug,
+            (_, 0) => log::LevelFilter::Trace,
+            (_, 1) => log::LevelFilter::Warn,
+            _ => log::LevelFilter::Error,
+        };
+        let colors = ColoredLevelConfig::new()
+            .error(Color::Red)
+            .warn(Color::Yellow)
+            .info(Color::Green)
+            .debug(Color::Cyan)
+            .trace(Color::BrightBlack);
+        dispatch = dispatch.chain(
+            fern::Dispatch::new()
+                .format(move |out, message, record| {
+                    out.finish(format_args!(
+                        "{} {:>5} {message}",
+                        chrono::Local::now().format("%H:%M:%S"),
+                        colors.color(record.level()),
+                    ))
+                })
+                .level(console_level)
+                .chain(std::io::stdout()),
+        );
+    }
+    dispatch.apply()?;
+
+    for (from, to, result) in migrated {
+        match result {
+            Ok(()) => log::info!("Moved {from:?} to {to:?}"),
+            Err(e) => log::warn!("Failed to move {from:?} to {to:?}: {e}"),
+        }
+    }
+
+    log::trace!("Args: {opt:?}");
+
+    let Opt {
+        command,
+        game,
+        game_dir,
+        primary_character,
+        log_file,
+        translations_file,
+        presence_strings,
+        discord_client_id,
+        area_assets,
+        account_name,
+        poesessid,
+        http_port,
+        ws_port,
+        obs_file,
+        webhook_url,
+        webhook_min_level,
+        milestone_levels,
+        milestone_action,
+        playtime_reminder,
+        playtime_reminder_action,
+        whisper_toasts,
+        whisper_toast_cooldown,
+        trade_state,
+        trade_burst,
+        trade_quiet_period,
+        whisper_counter,
+        map_counter,
+        show_game_version,
+        show_gateway,
+        show_ping,
+        show_seed,
+        party_hint,
+        level_bracket,
+        profile_button,
+        splits,
+        livesplit,
+        toggle_hotkey,
+        control,
+        exit_with_game,
+        background,
+        no_update_check,
+        patterns,
+        process_names,
+        process_check_interval,
+        log_poll_interval,
+        min_activity_interval,
+        verbose: _,
+        quiet: _,
+        config: _,
+        portable: _,
+        log_dir: _,
+        log_max_size_mb: _,
+        log_max_files: _,
+        log_max_age: _,
+        history_db,
+        no_history,
+        state_file,
+        no_state_file,
+        export_dir,
+        export_format,
+    } = opt;
+
+    log::debug!("Data directory: {:?} (portable: {})", paths.data_dir, paths.is_portable);
+    let history_db = history_db.unwrap_or_else(|| paths.data_dir.join("poe2-drpc.db"));
+    let state_file = state_file.unwrap_or_else(|| paths.data_dir.join("poe2-drpc-state.json"));
+
+    let (agent_listen, presenter) = match command {
+        Some(Command::Stats { days }) => return Ok(stats::print_stats(&history_db, days)?),
+        Some(Command::InstallAutostart) => return Ok(autostart::install("install-autostart")?),
+        Some(Command::UninstallAutostart) => return Ok(autostart::uninstall()?),
+        Some(Command::SelfUpdate) => return Ok(update::self_update()?),
+        Some(Command::AuditAssets) => {
+            let area_assets = area_assets.as_deref().map(AreaAssets::load).transpose()?;
+            return Ok(audit::audit_assets(game, &discord_client_id, area_assets.as_ref())?);
+        },
+        Some(Command::CheckLog { path }) => {
+            let translations =
+                load_translations(game, translations_file.as_deref(), &presence_strings)?;
+            let patterns = LogPatterns::new(&patterns)?;
+            return Ok(check_log(&path, &patterns, &translations)?);
+        },
+        #[cfg(feature = "gui")]
+        Some(Command::Settings) => return Ok(gui::run_settings(paths.config_file)?),
+        Some(Command::Agent { listen, token }) => {
+            // the events include whisper contents, don't hand them to anyone on the network
+            if !listen.ip().is_loopback() && token.is_none() {
+                return Err(anyhow::anyhow!("Listening on {listen} requires a --token").into());
             }
+            (Some((listen, token)), None)
+        },
+        Some(Command::Presenter { agent, token }) => (None, Some((agent, token))),
+        None => (None, None),
+    };
+    // the split table only has the Path of Exile 2 campaign, the timer would never start
+    if game == Game::Poe1 && (splits || livesplit.is_some()) {
+        return Err(anyhow::anyhow!("--splits and --livesplit only support Path of Exile 2").into());
+    }
+
+    if background && !is_detached {
+        let pid = background::detach()?;
+        log::info!(
+            "Continuing in the background (pid {pid}), logging to {:?}",
+            log_dir.join(logfile::LOG_FILE_NAME)
+        );
+        return Ok(());
+    }
+
+    if !no_update_check {
+        update::spawn_update_check();
+    }
+
+    let translations = load_translations(game, translations_file.as_deref(), &presence_strings)?;
+    log::trace!("Translations: {translations:#?}");
+
+    let patterns = LogPatterns::new(&patterns)?;
+
+    let area_assets = area_assets.map(|path| AreaAssets::load(&path)).transpose()?;
+
+    let rpc = DiscordIpcClient::new(&discord_client_id)?;
+    log::info!("Created discord ipc client");
+
+    let state = Arc::new(Mutex::new(SessionState::new()));
+    // the agent runs without discord, there is no activity of its own to clear
+    let discord_client_id = Some(discord_client_id).filter(|_| agent_listen.is_none());
+    crash::install_panic_hook(paths.data_dir.clone(), state.clone());
+
+    if let Some(port) = http_port {
+        http::spawn_status_server(port, ws_port, state.clone()).await?;
+    }
+
+    let bus = EventBus::default();
+    if let Some(port) = ws_port {
+        let addr = (Ipv4Addr::LOCALHOST, port).into();
+        ws::spawn_event_server(addr, None, bus.clone(), state.clone()).await?;
+    }
+    if let Some((addr, token)) = &agent_listen {
+        ws::spawn_event_server(*addr, token.clone(), bus.clone(), state.clone()).await?;
+        log::info!("Accepting presenters on {addr}");
+    }
+    let presence = Presence::new(PresenceOptions {
+        game,
+        trade_state,
+        trade_burst,
+        trade_quiet_period: trade_quiet_period.into(),
+        whisper_counter,
+        map_counter,
+        game_version: show_game_version,
+        gateway: show_gateway,
+        ping: show_ping,
+        seed: show_seed,
+        party_hint,
+        level_bracket,
+        profile_button: profile_button.zip(account_name.clone()),
+        area_assets,
+        strings: translations.strings.clone(),
+        splits,
+    });
+    let hidden = Arc::new(AtomicBool::new(false));
+    if let Some(hotkey) = toggle_hotkey {
+        let hidden = hidden.clone();
+        hotkey::spawn_hotkey_listener(hotkey, move || {
+            hidden.fetch_xor(true, Ordering::Relaxed);
+        })?;
+        log::info!("Registered the presence toggle hotkey");
+    }
+    if control {
+        control::spawn_control_server(Control {
+            hidden: hidden.clone(),
+            bus: bus.clone(),
+            state: state.clone(),
+        })?;
+    }
+    let mut workers = Vec::new();
+    // the presenter shows the presence for an agent, which runs without discord
+    if agent_listen.is_none() {
+        workers.push(discord::spawn_discord_client(
+            rpc,
+            presence,
+            min_activity_interval.into(),
+            hidden,
+            &bus,
+            state.clone(),
+        ));
+    }
+    if !obs_file.is_empty() {
+        workers.push(obs::spawn_text_writer(obs_file, &bus, state.clone()));
+    }
+    if let (Some(account_name), Some(poesessid)) = (account_name, poesessid) {
+        let api = CharacterApi { game, account_name, poesessid };
+        workers.push(character_api::spawn_character_fetcher(api, &bus, state.clone()));
+    }
+    let webhook = webhook_url.map(|url| Webhook { url, game });
+    if !milestone_action.is_empty() {
+        workers.push(milestones::spawn_milestone_notifier(
+            milestone_levels,
+            milestone_action,
+            webhook.clone(),
+            &bus,
+        ));
+    }
+    if !playtime_reminder.is_empty() {
+        workers.push(reminders::spawn_playtime_reminder(
+            playtime_reminder,
+            playtime_reminder_action,
+            webhook.clone(),
+            &bus,
+        ));
+    }
+    let last_presence = match no_state_file {
+        true => None,
+        false => {
+            let last_presence = LastPresence::load(&state_file);
+            workers.push(last_presence::spawn_saver(
+                state_file,
+                last_presence.clone().unwrap_or_default(),
+                &bus,
+            ));
+            last_presence
+        },
+    };
+    if let Some(webhook) = webhook {
+        let act_progress =
+            last_presence.as_ref().map(|p| p.act_progress.clone()).unwrap_or_default();
+        workers.push(webhook::spawn_notifier(webhook, webhook_min_level, act_progress, &bus));
+    }
+    workers.push(summary::spawn_reporter(&bus));
+    if !no_history {
+        workers.push(history::spawn_recorder(History::open(&history_db)?, &bus));
+        log::info!("Recording history to {history_db:?}");
+    }
+    if let Some(dir) = export_dir {
+        workers.push(export::spawn_exporter(dir, export_format, &bus));
+    }
+    if whisper_toasts {
+        workers.push(notify::spawn_whisper_notifier(whisper_toast_cooldown.into(), &bus));
+    }
+    if let Some(addr) = livesplit {
+        workers.push(livesplit::spawn_livesplit_client(addr, &bus));
+    }
+
+    let emit = |event: GameEvent| {
+        state::lock(&state).apply(&event);
+        bus.publish(event);
+    };
+
+    // workers recover from panics under their supervisor, one escaping this ends the tool
+    let tail = async {
+        match presenter {
+            Some((url, token)) => remote::run_presenter(&url, token.as_deref(), &emit).await,
+            None => {
+                let logs = get_game_logs(game, log_file, game_dir)?;
+                let options = TailOptions {
+                    extra_process_names: &process_names,
+                    process_check_interval: process_check_interval.into(),
+                    log_poll_interval: log_poll_interval.into(),
+                    exit_with_game,
+                    last_presence: last_presence.as_ref(),
+                };
+                let router = &Mutex::new(InstanceRouter::new(logs.len(), primary_character));
+                let tails = logs.iter().enumerate().map(|(index, log)| {
+                    tail_game_log(log, options, &patterns, &translations, move |event| {
+                        router
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .route(index, event)
+                            .into_iter()
+                            .for_each(emit)
+                    })
+                });
+                futures_util::future::try_join_all(tails).await?;
+            },
         }
+        Ok::<_, Box<dyn std::error::Error>>(())
+    };
+    let run = async {
+        tokio::select! {
+            result = tail => result.map(|()| false),
+            _ = tokio::signal::ctrl_c() => Ok(true),
+        }
+    };
+    let is_interrupted = crash::clear_presence_on_panic(discord_client_id.as_deref(), run).await?;
+
+    if is_interrupted {
+        log::info!("Interrupted, exiting");
+        // the session is cut short, ending it lets the workers clear the activity and write it out
+        if state::lock(&state).game_running {
+            emit(GameEvent::SessionEnded { ts: chrono::Utc::now().timestamp() });
+        }
+    } else {
+        log::info!("Game closed, exiting");
+    }
+    bus.close();
+    for worker in workers {
+        let _ = worker.await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_lines_missing_an_optional_group() {
+        let overrides = [
+            "joined_area=: (?:(\\w+)|A stranger) has joined the area.".parse().unwrap(),
+            "level_up=: (?:(\\w+) )?\\((\\w+)\\) is now level (\\d+)".parse().unwrap(),
+        ];
+        let patterns = LogPatterns::new(&overrides).unwrap();
+        let translations = load_translations(Game::Poe2, None, &[]).unwrap();
+        let parse = |line: String| {
+            parse_log_line(&patterns.match_line(&line), &patterns, &translations, &[])
+        };
+
+        let prefix = "2025/01/31 21:04:05 123456 abc [INFO Client 1234]";
+        assert!(parse(format!("{prefix} : A stranger has joined the area.")).is_none());
+        assert!(parse(format!("{prefix} : (Witch) is now level 2")).is_none());
+        assert!(matches!(
+            parse(format!("{prefix} : Tester has joined the area.")),
+            Some(GameEvent::PlayerJoined { username }) if username == "Tester"
+        ));
+    }
+
+    #[test]
+    fn command_line_flags_take_precedence_over_the_config() {
+        let config = ["--game-dir", "D:\\PoE2", "-q", "--process-name", "a.exe", "--show-ping"];
+        let cli = ["poe2-rpc", "--log-file", "Client.txt", "-v", "--process-name", "b.exe"];
+        let cli: Vec<OsString> = cli.into_iter().map(OsString::from).collect();
 
-        rpc.clear_activity()?;
-        log::trace!("Cleared activity");
+        let config = config.into_iter().map(String::from).collect();
+        let config = config::without_overridden(&Opt::command(), config, &cli);
+        assert_eq!(config, ["--show-ping"]);
 
-        rpc.close()?;
-        log::trace!("Disconnected from discord rpc");
+        let args = cli[..1].iter().cloned().chain(config.into_iter().map(OsString::from));
+        let opt = Opt::try_parse_from(args.chain(cli[1..].iter().cloned())).unwrap();
+        assert_eq!(
+            (opt.process_names, opt.verbose, opt.show_ping),
+            (vec!["b.exe".into()], 1, true)
+        );
     }
 }
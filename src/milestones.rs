@@ -0,0 +1,98 @@
+use clap::ValueEnum;
+use serde_json::json;
+use tokio::task::{self, JoinHandle};
+
+use crate::events::{EventBus, GameEvent};
+use crate::models::ClassInfo;
+use crate::webhook::Webhook;
+use crate::{notify, supervisor};
+
+const COLOR_MILESTONE: u32 = 0xaf6025;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MilestoneAction {
+    Toast,
+    Webhook,
+    Sound,
+}
+
+impl MilestoneAction {
+    /// Notifies about the milestone or reminder, blocking until sent
+    pub fn run(&self, title: &str, text: &str, webhook: Option<&Webhook>) -> anyhow::Result<()> {
+        match self {
+            Self::Toast => notify::show_toast(title, text),
+            Self::Sound => notify::play_sound(),
+            Self::Webhook => match webhook {
+                Some(webhook) => webhook
+                    .post_embed(json!({ "title": text, "color": COLOR_MILESTONE }))
+                    .map_err(|e| anyhow::anyhow!("{e}")),
+                None => Err(anyhow::anyhow!("No webhook url configured")),
+            },
+        }
+    }
+}
+
+/// Milestones the tracked character reached going from `previous` to `current`
+fn get_milestones(previous: &ClassInfo, current: &ClassInfo, levels: &[u16]) -> Vec<String> {
+    if previous.username != current.username {
+        return Vec::new();
+    }
+
+    let mut milestones: Vec<String> = levels
+        .iter()
+        .filter(|&&level| previous.level < level && current.level >= level)
+        .map(|level| format!("{} reached level {level}", current.username))
+        .collect();
+    if let (None, Some(ascd)) = (&previous.ascendency, &current.ascendency) {
+        milestones.push(format!("{} ascended to {ascd}", current.username));
+    }
+    milestones
+}
+
+pub fn spawn_milestone_notifier(
+    levels: Vec<u16>,
+    actions: Vec<MilestoneAction>,
+    webhook: Option<Webhook>,
+    bus: &EventBus,
+) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("milestone notifier", async move || -> anyhow::Result<()> {
+        let mut character: Option<ClassInfo> = None;
+
+        while let Some(event) = rx.recv().await {
+            let class_info = match event {
+                GameEvent::SessionStarted { character: class_info, .. } => {
+                    character = class_info;
+                    continue;
+                },
+                // the level the character already had, nothing was reached just now
+                GameEvent::CharacterUpdated(class_info) => {
+                    character = Some(class_info);
+                    continue;
+                },
+                GameEvent::LevelUp { character: class_info, .. } => class_info,
+                _ => continue,
+            };
+
+            let milestones = character
+                .replace(class_info.clone())
+                .map(|previous| get_milestones(&previous, &class_info, &levels))
+                .unwrap_or_default();
+
+            for milestone in milestones {
+                log::info!("Milestone: {milestone}");
+
+                for action in &actions {
+                    let result = task::block_in_place(|| {
+                        action.run("Milestone reached", &milestone, webhook.as_ref())
+                    });
+                    if let Err(e) = result {
+                        log::warn!("Failed to run milestone action {action:?}: {e}");
+                    }
+                }
+            }
+        }
+        Ok(())
+    }))
+}
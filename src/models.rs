@@ -3,9 +3,11 @@ use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
 use regex::Captures;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+use crate::game::Game;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CharacterClass {
     Mercenary,
     Monk,
@@ -13,6 +15,12 @@ pub enum CharacterClass {
     Sorceress,
     Warrior,
     Witch,
+    // poe1 only
+    Marauder,
+    Duelist,
+    Shadow,
+    Templar,
+    Scion,
 }
 
 impl FromStr for CharacterClass {
@@ -26,6 +34,11 @@ impl FromStr for CharacterClass {
             "sorceress" => Ok(Self::Sorceress),
             "warrior" => Ok(Self::Warrior),
             "witch" => Ok(Self::Witch),
+            "marauder" => Ok(Self::Marauder),
+            "duelist" => Ok(Self::Duelist),
+            "shadow" => Ok(Self::Shadow),
+            "templar" => Ok(Self::Templar),
+            "scion" => Ok(Self::Scion),
             _ => Err(()),
         }
     }
@@ -38,16 +51,46 @@ impl fmt::Display for CharacterClass {
 }
 
 impl CharacterClass {
-    pub fn get_ascendencies(&self) -> Option<[ClassAscendency; 2]> {
-        match self {
-            Self::Mercenary => {
-                Some([ClassAscendency::Witchhunter, ClassAscendency::GemlingLegionnaire])
-            },
-            Self::Monk => Some([ClassAscendency::AcolyteOfChayula, ClassAscendency::Invoker]),
-            Self::Ranger => Some([ClassAscendency::Deadeye, ClassAscendency::Pathfinder]),
-            Self::Sorceress => Some([ClassAscendency::Chronomancer, ClassAscendency::Stormweaver]),
-            Self::Warrior => Some([ClassAscendency::Titan, ClassAscendency::Warbringer]),
-            Self::Witch => Some([ClassAscendency::BloodMage, ClassAscendency::Infernalist]),
+    pub fn get_classes(game: Game) -> &'static [Self] {
+        match game {
+            Game::Poe2 => &[
+                Self::Mercenary,
+                Self::Monk,
+                Self::Ranger,
+                Self::Sorceress,
+                Self::Warrior,
+                Self::Witch,
+            ],
+            Game::Poe1 => &[
+                Self::Marauder,
+                Self::Duelist,
+                Self::Ranger,
+                Self::Shadow,
+                Self::Witch,
+                Self::Templar,
+                Self::Scion,
+            ],
+        }
+    }
+
+    pub fn get_ascendencies(&self, game: Game) -> &'static [ClassAscendency] {
+        use ClassAscendency::*;
+
+        match (game, self) {
+            (Game::Poe2, Self::Mercenary) => &[Witchhunter, GemlingLegionnaire],
+            (Game::Poe2, Self::Monk) => &[AcolyteOfChayula, Invoker],
+            (Game::Poe2, Self::Ranger) => &[Deadeye, Pathfinder],
+            (Game::Poe2, Self::Sorceress) => &[Chronomancer, Stormweaver],
+            (Game::Poe2, Self::Warrior) => &[Titan, Warbringer],
+            (Game::Poe2, Self::Witch) => &[BloodMage, Infernalist],
+            (Game::Poe1, Self::Marauder) => &[Juggernaut, Berserker, Chieftain],
+            (Game::Poe1, Self::Duelist) => &[Slayer, Gladiator, Champion],
+            (Game::Poe1, Self::Ranger) => &[Deadeye, Warden, Pathfinder],
+            (Game::Poe1, Self::Shadow) => &[Assassin, Trickster, Saboteur],
+            (Game::Poe1, Self::Witch) => &[Necromancer, Occultist, Elementalist],
+            (Game::Poe1, Self::Templar) => &[Inquisitor, Hierophant, Guardian],
+            (Game::Poe1, Self::Scion) => &[Ascendant],
+            _ => &[],
         }
     }
 
@@ -59,11 +102,16 @@ impl CharacterClass {
             Self::Sorceress => "sorceress",
             Self::Warrior => "warrior",
             Self::Witch => "witch",
+            Self::Marauder => "marauder",
+            Self::Duelist => "duelist",
+            Self::Shadow => "shadow",
+            Self::Templar => "templar",
+            Self::Scion => "scion",
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClassAscendency {
     Witchhunter,
     GemlingLegionnaire,
@@ -77,6 +125,24 @@ pub enum ClassAscendency {
     Warbringer,
     BloodMage,
     Infernalist,
+    // poe1 only
+    Juggernaut,
+    Berserker,
+    Chieftain,
+    Slayer,
+    Gladiator,
+    Champion,
+    Warden,
+    Assassin,
+    Trickster,
+    Saboteur,
+    Necromancer,
+    Occultist,
+    Elementalist,
+    Inquisitor,
+    Hierophant,
+    Guardian,
+    Ascendant,
 }
 
 impl FromStr for ClassAscendency {
@@ -96,6 +162,23 @@ impl FromStr for ClassAscendency {
             "warbringer" => Ok(Self::Warbringer),
             "blood mage" => Ok(Self::BloodMage),
             "infernalist" => Ok(Self::Infernalist),
+            "juggernaut" => Ok(Self::Juggernaut),
+            "berserker" => Ok(Self::Berserker),
+            "chieftain" => Ok(Self::Chieftain),
+            "slayer" => Ok(Self::Slayer),
+            "gladiator" => Ok(Self::Gladiator),
+            "champion" => Ok(Self::Champion),
+            "warden" => Ok(Self::Warden),
+            "assassin" => Ok(Self::Assassin),
+            "trickster" => Ok(Self::Trickster),
+            "saboteur" => Ok(Self::Saboteur),
+            "necromancer" => Ok(Self::Necromancer),
+            "occultist" => Ok(Self::Occultist),
+            "elementalist" => Ok(Self::Elementalist),
+            "inquisitor" => Ok(Self::Inquisitor),
+            "hierophant" => Ok(Self::Hierophant),
+            "guardian" => Ok(Self::Guardian),
+            "ascendant" => Ok(Self::Ascendant),
             _ => Err(()),
         }
     }
@@ -116,6 +199,23 @@ impl Display for ClassAscendency {
             Self::Warbringer => write!(f, "Warbringer"),
             Self::BloodMage => write!(f, "Blood Mage"),
             Self::Infernalist => write!(f, "Infernalist"),
+            Self::Juggernaut => write!(f, "Juggernaut"),
+            Self::Berserker => write!(f, "Berserker"),
+            Self::Chieftain => write!(f, "Chieftain"),
+            Self::Slayer => write!(f, "Slayer"),
+            Self::Gladiator => write!(f, "Gladiator"),
+            Self::Champion => write!(f, "Champion"),
+            Self::Warden => write!(f, "Warden"),
+            Self::Assassin => write!(f, "Assassin"),
+            Self::Trickster => write!(f, "Trickster"),
+            Self::Saboteur => write!(f, "Saboteur"),
+            Self::Necromancer => write!(f, "Necromancer"),
+            Self::Occultist => write!(f, "Occultist"),
+            Self::Elementalist => write!(f, "Elementalist"),
+            Self::Inquisitor => write!(f, "Inquisitor"),
+            Self::Hierophant => write!(f, "Hierophant"),
+            Self::Guardian => write!(f, "Guardian"),
+            Self::Ascendant => write!(f, "Ascendant"),
         }
     }
 }
@@ -125,10 +225,16 @@ impl ClassAscendency {
         match self {
             Self::Witchhunter | Self::GemlingLegionnaire => CharacterClass::Mercenary,
             Self::AcolyteOfChayula | Self::Invoker => CharacterClass::Monk,
-            Self::Deadeye | Self::Pathfinder => CharacterClass::Ranger,
+            Self::Deadeye | Self::Pathfinder | Self::Warden => CharacterClass::Ranger,
             Self::Chronomancer | Self::Stormweaver => CharacterClass::Sorceress,
             Self::Titan | Self::Warbringer => CharacterClass::Warrior,
             Self::BloodMage | Self::Infernalist => CharacterClass::Witch,
+            Self::Necromancer | Self::Occultist | Self::Elementalist => CharacterClass::Witch,
+            Self::Juggernaut | Self::Berserker | Self::Chieftain => CharacterClass::Marauder,
+            Self::Slayer | Self::Gladiator | Self::Champion => CharacterClass::Duelist,
+            Self::Assassin | Self::Trickster | Self::Saboteur => CharacterClass::Shadow,
+            Self::Inquisitor | Self::Hierophant | Self::Guardian => CharacterClass::Templar,
+            Self::Ascendant => CharacterClass::Scion,
         }
     }
 
@@ -146,26 +252,248 @@ impl ClassAscendency {
             Self::Warbringer => "warrior_warbringer",
             Self::BloodMage => "witch_blood_mage",
             Self::Infernalist => "witch_infernalist",
+            Self::Juggernaut => "marauder_juggernaut",
+            Self::Berserker => "marauder_berserker",
+            Self::Chieftain => "marauder_chieftain",
+            Self::Slayer => "duelist_slayer",
+            Self::Gladiator => "duelist_gladiator",
+            Self::Champion => "duelist_champion",
+            Self::Warden => "ranger_warden",
+            Self::Assassin => "shadow_assassin",
+            Self::Trickster => "shadow_trickster",
+            Self::Saboteur => "shadow_saboteur",
+            Self::Necromancer => "witch_necromancer",
+            Self::Occultist => "witch_occultist",
+            Self::Elementalist => "witch_elementalist",
+            Self::Inquisitor => "templar_inquisitor",
+            Self::Hierophant => "templar_hierophant",
+            Self::Guardian => "templar_guardian",
+            Self::Ascendant => "scion_ascendant",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AreaKind {
+    Town,
+    Hideout,
+    Campaign,
+    Map,
+    /// Pinnacle and uber boss arenas, see [`get_pinnacle_boss`]
+    BossArena,
+    /// League mechanic domains and sub-areas, see [`LeagueMechanic`]
+    Mechanic,
+    Other,
+}
+
+impl AreaKind {
+    pub fn from_code(code: &str) -> Self {
+        let campaign_code = code.strip_prefix("C_").unwrap_or(code);
+        // poe2 campaign areas look like G1_4, poe1 ones like 1_1_4 (part, act, area)
+        let is_campaign = campaign_code
+            .strip_prefix('G')
+            .unwrap_or(campaign_code)
+            .split('_')
+            .next()
+            .is_some_and(|n| n.parse::<u8>().is_ok());
+
+        if code.starts_with("Hideout") {
+            Self::Hideout
+        } else if campaign_code.to_lowercase().ends_with("_town") {
+            Self::Town
+        } else if code.starts_with("MapUberBoss_") || get_pinnacle_boss(code).is_some() {
+            Self::BossArena
+        } else if LeagueMechanic::from_code(code).is_some() {
+            Self::Mechanic
+        } else if code.starts_with("Map") {
+            Self::Map
+        } else if is_campaign {
+            Self::Campaign
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Tier of an endgame map, tier 1 maps are area level 65 for poe2 waystones and 68 for poe1
+    /// maps (the MapWorlds areas)
+    pub fn get_map_tier(&self, code: &str, area_level: u16) -> Option<u16> {
+        let tier_one_level = match code.starts_with("MapWorlds") {
+            true => 68,
+            false => 65,
+        };
+        (*self == Self::Map && area_level >= tier_one_level)
+            .then(|| area_level - tier_one_level + 1)
+    }
+}
+
+/// Area code prefixes of pinnacle boss arenas with the boss fought in them
+const PINNACLE_ARENAS: &[(&str, &str)] = &[
+    ("MapUberBoss_Monolith", "Arbiter of Ash"),
+    ("MapWorldsShapersRealm", "The Shaper"),
+    ("MapWorldsElderArenaUber", "The Uber Elder"),
+    ("MapWorldsElderArena", "The Elder"),
+];
+
+/// Boss fought in a pinnacle arena, arenas without a known boss go by their area name
+pub fn get_pinnacle_boss(code: &str) -> Option<&'static str> {
+    PINNACLE_ARENAS.iter().find(|(prefix, _)| code.starts_with(prefix)).map(|(_, boss)| *boss)
+}
+
+/// League mechanic with areas of its own, e.g. breach domains or delirium simulacra
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeagueMechanic {
+    Breach,
+    Delirium,
+    Expedition,
+    Ritual,
+    Sanctum,
+    Abyss,
+    Ultimatum,
+}
+
+impl LeagueMechanic {
+    /// Area code fragments of each mechanic's areas, e.g. BreachDomain or AfflictionTown1
+    const AREA_CODES: &[(&str, Self)] = &[
+        ("Breach", Self::Breach),
+        ("Affliction", Self::Delirium),
+        ("Delirium", Self::Delirium),
+        ("Expedition", Self::Expedition),
+        ("Ritual", Self::Ritual),
+        ("Sanctum", Self::Sanctum),
+        ("Abyss", Self::Abyss),
+        ("Ultimatum", Self::Ultimatum),
+    ];
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::AREA_CODES
+            .iter()
+            .find(|(fragment, _)| code.contains(fragment))
+            .map(|(_, mechanic)| *mechanic)
+    }
+}
+
+impl Display for LeagueMechanic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Text fragments of the presence, `{placeholders}` are filled in with [`fill_template`]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PresenceStrings {
+    pub cruel: String,
+    pub level: String,
+    pub level_bracket: String,
+    pub trading_in_hideout: String,
+    pub party: String,
+    pub trade_whisper: String,
+    pub trade_whispers: String,
+    pub ping: String,
+    pub seed: String,
+    pub character_select: String,
+    pub maps: String,
+    pub mechanic: String,
+    pub boss_attempt: String,
+}
+
+impl Default for PresenceStrings {
+    fn default() -> Self {
+        Self {
+            cruel: "Cruel {area}".to_string(),
+            level: "{name} ({level})".to_string(),
+            level_bracket: "{level}+".to_string(),
+            trading_in_hideout: "Trading in hideout".to_string(),
+            party: "Party of {count}".to_string(),
+            trade_whisper: "1 trade whisper".to_string(),
+            trade_whispers: "{count} trade whispers".to_string(),
+            ping: "ping ~{ms} ms".to_string(),
+            seed: "seed {seed}".to_string(),
+            character_select: "In character select".to_string(),
+            maps: "Maps this session: {count}".to_string(),
+            mechanic: "{mechanic}: {name} ({level})".to_string(),
+            boss_attempt: "{boss} – attempt {count}".to_string(),
+        }
+    }
+}
+
+impl PresenceStrings {
+    /// The fragments by name, as used by `--presence-string`
+    pub fn fields_mut(&mut self) -> [(&'static str, &mut String); 13] {
+        [
+            ("cruel", &mut self.cruel),
+            ("level", &mut self.level),
+            ("level_bracket", &mut self.level_bracket),
+            ("trading_in_hideout", &mut self.trading_in_hideout),
+            ("party", &mut self.party),
+            ("trade_whisper", &mut self.trade_whisper),
+            ("trade_whispers", &mut self.trade_whispers),
+            ("ping", &mut self.ping),
+            ("seed", &mut self.seed),
+            ("character_select", &mut self.character_select),
+            ("maps", &mut self.maps),
+            ("mechanic", &mut self.mechanic),
+            ("boss_attempt", &mut self.boss_attempt),
+        ]
+    }
+
+    pub fn apply_overrides(&mut self, overrides: &[StringOverride]) {
+        for (name, template) in self.fields_mut() {
+            if let Some(o) = overrides.iter().rev().find(|o| o.name == name) {
+                template.clone_from(&o.template);
+            }
         }
     }
 }
 
+/// Replacement for a presence text fragment, e.g. `character_select=Picking a character`
+#[derive(Debug, Clone)]
+pub struct StringOverride {
+    pub name: String,
+    pub template: String,
+}
+
+impl FromStr for StringOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, template) = s.split_once('=').ok_or("expected NAME=TEMPLATE")?;
+        let mut strings = PresenceStrings::default();
+        let names = strings.fields_mut().map(|(n, _)| n);
+        if !names.contains(&name) {
+            return Err(format!("unknown string '{name}', expected one of: {}", names.join(", ")));
+        }
+        Ok(Self { name: name.to_owned(), template: template.to_owned() })
+    }
+}
+
+pub fn fill_template(template: &str, values: &[(&str, &dyn Display)]) -> String {
+    values.iter().fold(template.to_owned(), |text, (key, value)| {
+        text.replace(&format!("{{{key}}}"), &value.to_string())
+    })
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Translations {
     pub areas: HashMap<String, String>,
+    /// Class and ascendency names of localized game clients, mapped to their english names
+    #[serde(default)]
+    pub classes: HashMap<String, String>,
+    #[serde(default)]
+    pub strings: PresenceStrings,
 }
 
 impl Translations {
     pub fn get_area_display_name(&self, area: &str) -> Option<String> {
         let (name, is_cruel) = area.strip_prefix("C_").map_or((area, false), |s| (s, true));
         self.areas.get(name).map(|area_name| match is_cruel {
-            true => format!("Cruel {area_name}"),
+            true => fill_template(&self.strings.cruel, &[("area", area_name)]),
             false => area_name.to_owned(),
         })
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ClassInfo {
     pub class: CharacterClass,
     pub ascendency: Option<ClassAscendency>,
@@ -174,19 +502,40 @@ pub struct ClassInfo {
 }
 
 impl ClassInfo {
-    pub fn parse_from_capture(caps: &Captures, user_blacklist: &[String]) -> Option<Self> {
-        let username = caps.get(1).map_or("", |m| m.as_str());
-        let class = caps.get(2).map_or("", |m| m.as_str());
-        let level = caps.get(3).map_or(0, |m| m.as_str().parse::<u16>().unwrap());
+    pub fn parse_from_capture(
+        caps: &Captures,
+        translations: &Translations,
+        user_blacklist: &[String],
+    ) -> Option<Self> {
+        let username = caps.get(1)?.as_str();
+        let class = caps.get(2)?.as_str();
+        let class = translations.classes.get(class).map_or(class, String::as_str);
 
         if user_blacklist.contains(&username.to_owned()) {
             return None;
         }
 
+        let Some(level) = caps.get(3).and_then(|m| m.as_str().parse::<u16>().ok()) else {
+            log::warn!("Skipping level up of {username} with invalid level: {:?}", &caps[0]);
+            return None;
+        };
+
+        Self::from_parts(username, class, level)
+    }
+
+    /// Character from its name, class or ascendency name and level
+    pub fn from_parts(username: &str, class: &str, level: u16) -> Option<Self> {
         let ascd_class = ClassAscendency::from_str(class).ok();
-        let main_class = ascd_class
-            .clone()
-            .map_or_else(|| CharacterClass::from_str(class).unwrap(), |ascd| ascd.get_class());
+        let main_class = match &ascd_class {
+            Some(ascd) => ascd.get_class(),
+            None => match CharacterClass::from_str(class) {
+                Ok(class) => class,
+                Err(()) => {
+                    log::warn!("Skipping level up of {username} with unknown class {class}");
+                    return None;
+                },
+            },
+        };
 
         Some(Self {
             class: main_class,
@@ -197,23 +546,51 @@ impl ClassInfo {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MapChangeInfo {
     pub level: u16,
+    pub code: String,
     pub name: String,
+    pub kind: AreaKind,
     pub seed: u64,
     pub ts: i64,
 }
 
 impl MapChangeInfo {
-    pub fn parse_from_captures(caps: &Captures, translations: &Translations) -> Self {
-        let level = caps.get(1).map_or(0, |m| m.as_str().parse::<u16>().unwrap());
-        let name = caps.get(2).map_or("", |m| m.as_str());
-        let seed = caps.get(3).map_or(0, |m| m.as_str().parse::<u64>().unwrap());
+    pub fn parse_from_captures(
+        caps: &Captures,
+        translations: &Translations,
+        ts: i64,
+    ) -> Option<Self> {
+        let code = caps.get(2)?.as_str();
+        let (Some(level), Some(seed)) = (
+            caps.get(1).and_then(|m| m.as_str().parse::<u16>().ok()),
+            caps.get(3).and_then(|m| m.as_str().parse::<u64>().ok()),
+        ) else {
+            log::warn!("Skipping area change with invalid level or seed: {:?}", &caps[0]);
+            return None;
+        };
+
+        let name = translations.get_area_display_name(code).unwrap_or(code.to_owned());
+
+        Some(Self { level, code: code.to_owned(), name, kind: AreaKind::from_code(code), seed, ts })
+    }
 
-        let name = translations.get_area_display_name(name).unwrap_or(name.to_owned());
-        let ts = chrono::Utc::now().timestamp();
+    /// Whether both area changes generated the same instance, e.g. logging back into a map
+    pub fn is_same_instance(&self, other: &Self) -> bool {
+        self.code == other.code && self.seed == other.seed
+    }
+
+    pub fn is_cruel(&self) -> bool {
+        self.code.starts_with("C_")
+    }
 
-        Self { level, name: name.to_owned(), seed, ts }
+    pub fn get_act(&self) -> Option<u8> {
+        let code = self.code.strip_prefix("C_").unwrap_or(&self.code);
+        match code.strip_prefix('G') {
+            Some(code) => code.split('_').next()?.parse().ok(),
+            None if self.kind == AreaKind::Campaign => code.split('_').nth(1)?.parse().ok(),
+            None => None,
+        }
     }
 }
@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+use tokio::task::{self, JoinHandle};
+
+use crate::events::{EventBus, GameEvent};
+use crate::supervisor;
+
+#[cfg(windows)]
+pub fn show_toast(title: &str, text: &str) -> anyhow::Result<()> {
+    use tauri_winrt_notification::{Duration, Sound, Toast};
+
+    Toast::new(Toast::POWERSHELL_APP_ID)
+        .title(title)
+        .text1(text)
+        .sound(Some(Sound::IM))
+        .duration(Duration::Short)
+        .show()?;
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn show_toast(_title: &str, _text: &str) -> anyhow::Result<()> {
+    anyhow::bail!("Toast notifications are only supported on windows")
+}
+
+#[cfg(windows)]
+pub fn play_sound() -> anyhow::Result<()> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    Command::new("powershell")
+        .args(["-NoProfile", "-Command", "[System.Media.SystemSounds]::Exclamation.Play()"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()?;
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn play_sound() -> anyhow::Result<()> {
+    anyhow::bail!("Notification sounds are only supported on windows")
+}
+
+pub fn spawn_whisper_notifier(cooldown: Duration, bus: &EventBus) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("whisper notifier", async move || -> anyhow::Result<()> {
+        let mut last_toast: Option<Instant> = None;
+
+        while let Some(event) = rx.recv().await {
+            let GameEvent::Whisper { from, message, .. } = event else {
+                continue;
+            };
+
+            if last_toast.is_some_and(|t| t.elapsed() < cooldown) {
+                log::debug!("Skipping whisper toast from {from}, still on cooldown");
+                continue;
+            }
+            last_toast = Some(Instant::now());
+
+            let title = format!("Whisper from {from}");
+            if let Err(e) = task::block_in_place(|| show_toast(&title, &message)) {
+                log::warn!("Failed to show whisper toast: {e}");
+            }
+        }
+        Ok(())
+    }))
+}
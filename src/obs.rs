@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use tokio::fs;
+use tokio::task::JoinHandle;
+
+use crate::events::{EventBus, GameEvent};
+use crate::state::{self, SessionState};
+use crate::supervisor;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ObsField {
+    Character,
+    Class,
+    Area,
+    Level,
+    Presence,
+}
+
+impl FromStr for ObsField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "character" => Ok(Self::Character),
+            "class" => Ok(Self::Class),
+            "area" => Ok(Self::Area),
+            "level" => Ok(Self::Level),
+            "presence" => Ok(Self::Presence),
+            _ => Err(format!(
+                "unknown field '{s}', expected one of: character, class, area, level, presence"
+            )),
+        }
+    }
+}
+
+impl ObsField {
+    pub fn render(&self, state: &SessionState) -> String {
+        let character = state.character.as_ref();
+        let area = state.area.as_ref();
+        match self {
+            Self::Character => character.map(|c| c.username.clone()).unwrap_or_default(),
+            Self::Class => character
+                .map(|c| c.ascendency.as_ref().map_or(c.class.to_string(), |a| a.to_string()))
+                .unwrap_or_default(),
+            Self::Area => area.map(|a| a.name.clone()).unwrap_or_default(),
+            Self::Level => character.map(|c| c.level.to_string()).unwrap_or_default(),
+            Self::Presence => [
+                Self::Character.render(state),
+                character
+                    .map(|c| format!("{} ({})", Self::Class.render(state), c.level))
+                    .unwrap_or_default(),
+                area.map(|a| format!("{} ({})", a.name, a.level)).unwrap_or_default(),
+            ]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" - "),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObsFile {
+    pub field: ObsField,
+    pub path: PathBuf,
+}
+
+impl FromStr for ObsFile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, path) = s.split_once('=').ok_or("expected FIELD=PATH")?;
+        Ok(Self { field: field.parse()?, path: PathBuf::from(path) })
+    }
+}
+
+pub fn spawn_text_writer(
+    files: Vec<ObsFile>,
+    bus: &EventBus,
+    state: Arc<Mutex<SessionState>>,
+) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+    let mut contents = vec![None::<String>; files.len()];
+
+    tokio::spawn(supervisor::run("obs text writer", async move || -> anyhow::Result<()> {
+        while let Some(event) = rx.recv().await {
+            let state = match event {
+                GameEvent::SessionEnded { .. } => SessionState::default(),
+                _ => state::lock(&state).clone(),
+            };
+
+            for (file, last) in files.iter().zip(contents.iter_mut()) {
+                let text = file.field.render(&state);
+                if last.as_ref() == Some(&text) {
+                    continue;
+                }
+
+                match fs::write(&file.path, &text).await {
+                    Ok(_) => *last = Some(text),
+                    Err(e) => log::warn!("Failed to write obs text file {:?}: {e}", file.path),
+                }
+            }
+        }
+        Ok(())
+    }))
+}
@@ -0,0 +1,103 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use anyhow::Context;
+use directories::ProjectDirs;
+
+use crate::config::CONFIG_FILE;
+
+/// Files older versions kept in the working directory, by the name they have in the data directory
+const LEGACY_DATA_FILES: &[&str] = &["poe2-drpc.db", "poe2-drpc-state.json"];
+
+/// Where the config file and the files the tool writes (state, history, logs) live. Portable
+/// installs keep everything next to the executable, others use the platform's app data
+/// directories (e.g. %APPDATA%\poe2-drpc on windows).
+#[derive(Debug, Clone)]
+pub struct DataPaths {
+    pub config_file: PathBuf,
+    pub data_dir: PathBuf,
+    pub is_portable: bool,
+    is_default_config: bool,
+}
+
+impl DataPaths {
+    /// Resolves the paths from the raw command line, they are needed before the config file is
+    /// parsed along with the rest of the flags. A config file next to the executable implies
+    /// portable mode so existing setups keep their files.
+    pub fn from_args(args: &[OsString]) -> anyhow::Result<Self> {
+        let mut config_file = None;
+        let mut is_portable = false;
+
+        let mut args = args.iter().skip(1).map(|arg| arg.to_string_lossy());
+        while let Some(arg) = args.next() {
+            match arg.as_ref() {
+                "--portable" => is_portable = true,
+                "--config" => config_file = args.next().map(|path| PathBuf::from(path.as_ref())),
+                arg => {
+                    if let Some(path) = arg.strip_prefix("--config=") {
+                        config_file = Some(PathBuf::from(path));
+                    }
+                },
+            }
+        }
+
+        let exe = env::current_exe()?;
+        let exe_dir = exe.parent().context("Executable has no parent directory")?;
+        let is_portable = is_portable || exe_dir.join(CONFIG_FILE).exists();
+
+        let (config_dir, data_dir) = match is_portable {
+            true => (exe_dir.to_path_buf(), exe_dir.to_path_buf()),
+            false => {
+                let dirs = ProjectDirs::from("", "", "poe2-drpc")
+                    .context("No home directory to keep the tool's files in, use --portable")?;
+                (dirs.config_dir().to_path_buf(), dirs.data_dir().to_path_buf())
+            },
+        };
+
+        Ok(Self {
+            is_default_config: config_file.is_none(),
+            config_file: config_file.unwrap_or_else(|| config_dir.join(CONFIG_FILE)),
+            data_dir,
+            is_portable,
+        })
+    }
+
+    /// Moves the config, history and state files older versions left in the working directory
+    /// to where they are kept now, unless there already are files there. Runs before logging is
+    /// set up, so returns what was moved along with the outcome.
+    pub fn migrate_legacy_files(&self) -> Vec<(PathBuf, PathBuf, io::Result<()>)> {
+        let Ok(cwd) = env::current_dir() else {
+            return Vec::new();
+        };
+
+        let mut moves = Vec::new();
+        if self.is_default_config {
+            moves.push((cwd.join(CONFIG_FILE), self.config_file.clone()));
+        }
+        for name in LEGACY_DATA_FILES {
+            moves.push((cwd.join(name), self.data_dir.join(name)));
+        }
+
+        moves
+            .into_iter()
+            .filter(|(from, to)| from.is_file() && !to.exists())
+            .map(|(from, to)| {
+                let result = move_file(&from, &to);
+                (from, to, result)
+            })
+            .collect()
+    }
+}
+
+/// Renames the file, copying it when that fails (e.g. moving to another drive)
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use regex::{Captures, Regex, RegexSet, SetMatches};
+
+/// Built-in log patterns by name, the name is what `--pattern` overrides refer to
+const DEFAULT_PATTERNS: &[(&str, &str)] = &[
+    ("generating_area", r#"] Generating level (\d+) area "([^"]+)" with seed (\d+)"#),
+    ("joined_area", r#": (\w+) has joined the area."#),
+    ("left_area", r#": (\w+) has left the area."#),
+    ("slain", r#": (\w+) has been slain."#),
+    ("whisper", r#"@From (?:<[^>]*> )?([^:]+): (.*)"#),
+    ("trade_whisper", r#"^Hi, I(?: would|'d) like to buy your"#),
+    ("level_up", r#": (\w+) \((\w+)\) is now level (\d+)"#),
+    ("game_version", r#"\] (?:Client )?[Vv]ersion:? v?(\d+\.\d+\.\d+\w*)"#),
+    ("character_select", r#"\] Connect(?:ed|ing) to [\w-]+\.login\."#),
+    (
+        "server_connect",
+        r#"\] Connect(?:ed|ing) to (?:instance server at )?([\w.-]+)(?::\d+)?(?: in (\d+) ?ms)?"#,
+    ),
+];
+
+/// Patterns tried against every new log line, matched in a single pass by
+/// [`LogPatterns::match_line`]
+const LINE_PATTERNS: &[&str] = &[
+    "level_up",
+    "generating_area",
+    "joined_area",
+    "whisper",
+    "left_area",
+    "game_version",
+    "server_connect",
+    "slain",
+    "character_select",
+];
+
+/// When a log line was written, from its `2025/01/31 21:04:05` prefix in the local time zone
+pub fn get_line_timestamp(line: &str) -> Option<i64> {
+    let time = NaiveDateTime::parse_from_str(line.get(..19)?, "%Y/%m/%d %H:%M:%S").ok()?;
+    // the earlier of the two times an hour repeats at the end of daylight saving time
+    Local.from_local_datetime(&time).earliest().map(|time| time.timestamp())
+}
+
+/// When the game last opened its log, which it does on every launch
+pub fn get_log_opened_at(log: &str) -> Option<i64> {
+    log.lines()
+        .rev()
+        .find(|line| line.contains("***** LOG FILE OPENING *****"))
+        .and_then(get_line_timestamp)
+}
+
+/// Replacement for a built-in log pattern, e.g. `level_up=: (\w+) \((\w+)\) is level (\d+)`
+#[derive(Debug, Clone)]
+pub struct PatternOverride {
+    name: String,
+    pattern: String,
+}
+
+impl FromStr for PatternOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, pattern) = s.split_once('=').ok_or("expected NAME=REGEX")?;
+        if !DEFAULT_PATTERNS.iter().any(|(n, _)| *n == name) {
+            let names: Vec<_> = DEFAULT_PATTERNS.iter().map(|(n, _)| *n).collect();
+            return Err(format!("unknown pattern '{name}', expected one of: {}", names.join(", ")));
+        }
+        Ok(Self { name: name.to_owned(), pattern: pattern.to_owned() })
+    }
+}
+
+#[derive(Debug)]
+pub struct LogPatterns {
+    pub generating_area: Regex,
+    pub joined_area: Regex,
+    pub left_area: Regex,
+    pub slain: Regex,
+    pub whisper: Regex,
+    pub trade_whisper: Regex,
+    pub level_up: Regex,
+    pub game_version: Regex,
+    pub server_connect: Regex,
+    pub character_select: Regex,
+    line_set: RegexSet,
+}
+
+impl LogPatterns {
+    /// Built-in patterns with the overrides applied, overrides have to compile and capture as many
+    /// groups as the pattern they replace
+    pub fn new(overrides: &[PatternOverride]) -> anyhow::Result<Self> {
+        let get = |name: &str| -> anyhow::Result<Regex> {
+            let (_, default) = DEFAULT_PATTERNS.iter().find(|(n, _)| *n == name).unwrap();
+            let default = Regex::new(default)?;
+            let Some(pattern) = overrides.iter().rev().find(|o| o.name == name) else {
+                return Ok(default);
+            };
+
+            let regex = Regex::new(&pattern.pattern)
+                .with_context(|| format!("Invalid {name} pattern override"))?;
+            if regex.captures_len() != default.captures_len() {
+                bail!(
+                    "The {name} pattern override has {} capture groups, expected {}",
+                    regex.captures_len() - 1,
+                    default.captures_len() - 1
+                );
+            }
+            log::info!("Overriding the {name} pattern with {regex}");
+            Ok(regex)
+        };
+
+        let mut patterns = Self {
+            generating_area: get("generating_area")?,
+            joined_area: get("joined_area")?,
+            left_area: get("left_area")?,
+            slain: get("slain")?,
+            whisper: get("whisper")?,
+            trade_whisper: get("trade_whisper")?,
+            level_up: get("level_up")?,
+            game_version: get("game_version")?,
+            server_connect: get("server_connect")?,
+            character_select: get("character_select")?,
+            line_set: RegexSet::empty(),
+        };
+        let line_regexes = LINE_PATTERNS.iter().filter_map(|name| patterns.get_line_regex(name));
+        patterns.line_set = RegexSet::new(line_regexes.map(Regex::as_str))?;
+        Ok(patterns)
+    }
+
+    fn get_line_regex(&self, name: &str) -> Option<&Regex> {
+        match name {
+            "level_up" => Some(&self.level_up),
+            "generating_area" => Some(&self.generating_area),
+            "joined_area" => Some(&self.joined_area),
+            "whisper" => Some(&self.whisper),
+            "left_area" => Some(&self.left_area),
+            "game_version" => Some(&self.game_version),
+            "server_connect" => Some(&self.server_connect),
+            "slain" => Some(&self.slain),
+            "character_select" => Some(&self.character_select),
+            _ => None,
+        }
+    }
+
+    /// Captures of the first line pattern matching, trying one pattern after another. The
+    /// baseline `check-log` compares [`Self::match_line`] against.
+    pub fn captures_sequential<'a>(&self, line: &'a str) -> Option<Captures<'a>> {
+        LINE_PATTERNS
+            .iter()
+            .filter_map(|name| self.get_line_regex(name))
+            .find_map(|regex| regex.captures(line))
+    }
+
+    /// Matches the line against all line patterns at once, most lines match none of them
+    pub fn match_line<'a>(&'a self, line: &'a str) -> LineMatch<'a> {
+        LineMatch { patterns: self, line, matches: self.line_set.matches(line) }
+    }
+}
+
+/// Line patterns a log line matched, captures are only extracted for those
+pub struct LineMatch<'a> {
+    patterns: &'a LogPatterns,
+    line: &'a str,
+    matches: SetMatches,
+}
+
+impl<'a> LineMatch<'a> {
+    pub fn line(&self) -> &'a str {
+        self.line
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.matches.matched_any()
+    }
+
+    pub fn is_match(&self, name: &str) -> bool {
+        LINE_PATTERNS.iter().position(|n| *n == name).is_some_and(|i| self.matches.matched(i))
+    }
+
+    /// Captures of the first line pattern matching, like [`LogPatterns::captures_sequential`]
+    pub fn first_captures(&self) -> Option<Captures<'a>> {
+        LINE_PATTERNS.iter().find_map(|name| self.captures(name))
+    }
+
+    pub fn captures(&self, name: &str) -> Option<Captures<'a>> {
+        match self.is_match(name) {
+            true => self.patterns.get_line_regex(name)?.captures(self.line),
+            false => None,
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use discord_rich_presence::activity::{Activity, Assets, Button, Timestamps};
+
+use crate::area_assets::AreaAssets;
+use crate::events::GameEvent;
+use crate::game::Game;
+use crate::models::{fill_template, get_pinnacle_boss, AreaKind, LeagueMechanic, PresenceStrings};
+use crate::splits::SplitTimer;
+use crate::state::SessionState;
+
+/// Page the presence button links the played character to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProfileLink {
+    Profile,
+    Ninja,
+}
+
+impl ProfileLink {
+    fn get_label(&self) -> &'static str {
+        match self {
+            Self::Profile => "Character profile",
+            Self::Ninja => "Build on poe.ninja",
+        }
+    }
+
+    fn get_url(&self, game: Game, account_name: &str, character: &str) -> String {
+        match (self, game) {
+            (Self::Profile, _) => format!(
+                "{}/account/view-profile/{}/characters?characterName={character}",
+                game.get_website_url(),
+                account_name.replace('#', "%23")
+            ),
+            (Self::Ninja, Game::Poe1) => format!(
+                "https://poe.ninja/profile/{}/character/{character}",
+                account_name.replace('#', "-")
+            ),
+            (Self::Ninja, Game::Poe2) => format!(
+                "https://poe.ninja/poe2/profile/{}/character/{character}",
+                account_name.replace('#', "-")
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PresenceOptions {
+    pub game: Game,
+    pub trade_state: bool,
+    pub trade_burst: usize,
+    pub trade_quiet_period: Duration,
+    pub whisper_counter: bool,
+    pub map_counter: bool,
+    pub game_version: bool,
+    pub gateway: bool,
+    pub ping: bool,
+    pub seed: bool,
+    pub party_hint: bool,
+    pub level_bracket: Option<u16>,
+    pub profile_button: Option<(ProfileLink, String)>,
+    pub area_assets: Option<AreaAssets>,
+    pub strings: PresenceStrings,
+    pub splits: bool,
+}
+
+#[derive(Debug)]
+pub struct Presence {
+    options: PresenceOptions,
+    trade_whispers: VecDeque<Instant>,
+    is_trading: bool,
+    split_timer: SplitTimer,
+}
+
+impl Presence {
+    pub fn new(options: PresenceOptions) -> Self {
+        Self {
+            options,
+            trade_whispers: VecDeque::new(),
+            is_trading: false,
+            split_timer: SplitTimer::default(),
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GameEvent) {
+        if self.options.splits {
+            self.split_timer.on_event(event);
+        }
+
+        match event {
+            GameEvent::SessionStarted { .. } => {
+                self.trade_whispers.clear();
+                self.is_trading = false;
+            },
+            GameEvent::Whisper { is_trade: true, .. } if self.options.trade_state => {
+                self.trade_whispers.push_back(Instant::now());
+            },
+            _ => {},
+        }
+    }
+
+    /// Re-evaluates time based state, returns true if the activity needs to be rebuilt
+    pub fn tick(&mut self, state: &SessionState) -> bool {
+        if !self.options.trade_state {
+            return false;
+        }
+
+        while self
+            .trade_whispers
+            .front()
+            .is_some_and(|t| t.elapsed() > self.options.trade_quiet_period)
+        {
+            self.trade_whispers.pop_front();
+        }
+
+        let in_hideout = state.area.as_ref().is_some_and(|a| a.kind == AreaKind::Hideout);
+        let is_trading = in_hideout
+            && match self.is_trading {
+                true => !self.trade_whispers.is_empty(),
+                false => self.trade_whispers.len() >= self.options.trade_burst,
+            };
+
+        if is_trading != self.is_trading {
+            log::info!("Trading state changed: {is_trading}");
+            self.is_trading = is_trading;
+            return true;
+        }
+        false
+    }
+
+    /// Character level, rounded down to the level bracket if set (e.g. 90+)
+    fn format_level(&self, level: u16) -> String {
+        match self.options.level_bracket.filter(|&bracket| bracket > 1) {
+            Some(bracket) => fill_template(
+                &self.options.strings.level_bracket,
+                &[("level", &(level / bracket * bracket).max(1))],
+            ),
+            None => level.to_string(),
+        }
+    }
+
+    pub fn build_activity(&self, state: &SessionState) -> Activity {
+        let strings = &self.options.strings;
+        let mut activity = Activity::new();
+
+        // nothing about the last character or area is current while picking a character
+        if let Some(ts) = state.character_select_since {
+            let details = match &state.note {
+                Some(note) => format!("{} | {note}", strings.character_select),
+                None => strings.character_select.clone(),
+            };
+            return activity.details(details).timestamps(Timestamps::default().start(ts));
+        }
+
+        if let Some(class_info) = &state.character {
+            let mut details = match self.split_timer.get_current_split() {
+                Some(split) => format!("{} | {split}", class_info.username),
+                None => class_info.username.clone(),
+            };
+            if let Some(note) = &state.note {
+                details = format!("{details} | {note}");
+            }
+            activity = activity.details(details);
+
+            let whispers = match state.stats.trade_whispers {
+                0 => None,
+                1 => Some(strings.trade_whisper.clone()),
+                n => Some(fill_template(&strings.trade_whispers, &[("count", &n)])),
+            }
+            .filter(|_| self.options.whisper_counter);
+            let maps = Some(state.stats.maps_completed)
+                .filter(|&count| count > 0 && self.options.map_counter)
+                .map(|count| fill_template(&strings.maps, &[("count", &count)]));
+            let version = state.game_version.clone().filter(|_| self.options.game_version);
+            let gateway = state.gateway.clone().filter(|_| self.options.gateway);
+            let ping = state
+                .latency_ms
+                .filter(|_| self.options.ping)
+                .map(|ms| fill_template(&strings.ping, &[("ms", &ms)]));
+            let seed = state
+                .area
+                .as_ref()
+                .filter(|_| self.options.seed)
+                .map(|area_info| fill_template(&strings.seed, &[("seed", &area_info.seed)]));
+
+            let mut extras = Vec::new();
+            extras.extend(state.league.clone());
+            extras.extend(whispers);
+            extras.extend(maps);
+            extras.extend(version);
+            extras.extend(gateway);
+            extras.extend(ping);
+            extras.extend(seed);
+
+            let game = self.options.game;
+            let class_image =
+                game.get_discord_image_name(class_info.class.get_discord_image_name());
+            let level = self.format_level(class_info.level);
+            let (character_image, character_text) = match &class_info.ascendency {
+                Some(ascd) => (
+                    game.get_discord_image_name(ascd.get_discord_image_name()),
+                    fill_template(&strings.level, &[("name", ascd), ("level", &level)]),
+                ),
+                None => (
+                    class_image.clone(),
+                    fill_template(
+                        &strings.level,
+                        &[("name", &class_info.class), ("level", &level)],
+                    ),
+                ),
+            };
+
+            let area_asset = state.area.as_ref().and_then(|area_info| {
+                self.options.area_assets.as_ref().and_then(|a| a.get_asset(area_info))
+            });
+            let assets = match area_asset {
+                // custom area art takes the large image, the character moves to the small one
+                Some((area_image, area_text)) => {
+                    let small_text = std::iter::once(character_text).chain(extras);
+                    Assets::default()
+                        .large_image(area_image)
+                        .large_text(area_text)
+                        .small_image(character_image)
+                        .small_text(small_text.collect::<Vec<_>>().join(" | "))
+                },
+                None => {
+                    let mut assets =
+                        Assets::default().large_image(character_image).large_text(character_text);
+                    if class_info.ascendency.is_some() {
+                        extras.insert(0, class_info.class.to_string());
+                    }
+                    if !extras.is_empty() {
+                        assets = assets.small_image(class_image).small_text(extras.join(" | "));
+                    }
+                    assets
+                },
+            };
+
+            activity = activity.assets(assets);
+
+            if let Some((link, account_name)) = &self.options.profile_button {
+                let url = link.get_url(game, account_name, &class_info.username);
+                activity = activity.buttons(vec![Button::new(link.get_label(), url)]);
+            }
+        }
+
+        if let Some(instance_info) = &state.area {
+            let mechanic = LeagueMechanic::from_code(&instance_info.code)
+                .filter(|_| instance_info.kind == AreaKind::Mechanic);
+            let attempts = state
+                .boss_attempts
+                .get(&instance_info.code)
+                .filter(|_| instance_info.kind == AreaKind::BossArena);
+            let mut state_text = match (self.is_trading, mechanic, attempts) {
+                (true, _, _) => strings.trading_in_hideout.clone(),
+                (false, _, Some(attempts)) => {
+                    let boss = get_pinnacle_boss(&instance_info.code)
+                        .unwrap_or(instance_info.name.as_str());
+                    fill_template(&strings.boss_attempt, &[("boss", &boss), ("count", attempts)])
+                },
+                (false, Some(mechanic), _) => fill_template(
+                    &strings.mechanic,
+                    &[
+                        ("mechanic", &mechanic),
+                        ("name", &instance_info.name),
+                        ("level", &instance_info.level),
+                    ],
+                ),
+                (false, None, None) => fill_template(
+                    &strings.level,
+                    &[("name", &instance_info.name), ("level", &instance_info.level)],
+                ),
+            };
+            if self.options.party_hint && !state.party.is_empty() {
+                let party = fill_template(&strings.party, &[("count", &(state.party.len() + 1))]);
+                state_text = format!("{state_text} | {party}");
+            }
+            activity = activity.state(state_text).timestamps(
+                Timestamps::default()
+                    .start(self.split_timer.get_run_start().unwrap_or(instance_info.ts)),
+            );
+        }
+
+        activity
+    }
+}
@@ -0,0 +1,135 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{Datelike, Weekday};
+use tokio::task::{self, JoinHandle};
+
+use crate::events::{EventBus, GameEvent};
+use crate::milestones::MilestoneAction;
+use crate::supervisor;
+use crate::webhook::Webhook;
+
+/// Log silence after which the player is considered away and continuous play starts over
+const IDLE_RESET: Duration = Duration::from_secs(30 * 60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Play time after which to remind, on every day (`4h`) or on some weekdays (`sat,sun=6h`,
+/// `mon-fri=3h`)
+#[derive(Debug, Clone)]
+pub struct PlaytimeReminder {
+    days: Vec<Weekday>,
+    after: Duration,
+}
+
+impl FromStr for PlaytimeReminder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (days, after) = match s.split_once('=') {
+            Some((days, after)) => (parse_weekdays(days)?, after),
+            None => (Vec::new(), s),
+        };
+        let after = humantime::parse_duration(after.trim()).map_err(|e| e.to_string())?;
+        if after.is_zero() {
+            return Err("reminder duration can't be zero".to_string());
+        }
+        Ok(Self { days, after })
+    }
+}
+
+fn parse_weekday(s: &str) -> Result<Weekday, String> {
+    s.trim().parse().map_err(|_| format!("unknown weekday '{s}'"))
+}
+
+fn parse_weekdays(s: &str) -> Result<Vec<Weekday>, String> {
+    let mut days = Vec::new();
+    for part in s.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (mut day, last) = (parse_weekday(first)?, parse_weekday(last)?);
+                days.push(day);
+                while day != last {
+                    day = day.succ();
+                    days.push(day);
+                }
+            },
+            None => days.push(parse_weekday(part)?),
+        }
+    }
+    Ok(days)
+}
+
+/// Play time to remind after today, weekday specific reminders take precedence
+fn get_reminder_after(reminders: &[PlaytimeReminder], today: Weekday) -> Option<Duration> {
+    reminders
+        .iter()
+        .find(|r| r.days.contains(&today))
+        .or_else(|| reminders.iter().find(|r| r.days.is_empty()))
+        .map(|r| r.after)
+}
+
+/// Reminds of the time played without a break, again each time the same amount has passed
+pub fn spawn_playtime_reminder(
+    reminders: Vec<PlaytimeReminder>,
+    actions: Vec<MilestoneAction>,
+    webhook: Option<Webhook>,
+    bus: &EventBus,
+) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("playtime reminder", async move || -> anyhow::Result<()> {
+        // start of continuous play, last log activity and how many reminders were sent since
+        let mut playing: Option<(i64, i64, u32)> = None;
+
+        loop {
+            let event = tokio::time::timeout(CHECK_INTERVAL, rx.recv()).await;
+            let now = chrono::Utc::now().timestamp();
+            match event {
+                Ok(None) => return Ok(()),
+                Ok(Some(GameEvent::SessionEnded { .. })) => playing = None,
+                Ok(Some(GameEvent::SessionStarted { ts, .. })) => playing = Some((ts, now, 0)),
+                Ok(Some(_)) => {
+                    if let Some((started_at, last_activity, reminded)) = &mut playing {
+                        if now - *last_activity > IDLE_RESET.as_secs() as i64 {
+                            log::debug!("Back from a break, restarting the playtime reminder");
+                            (*started_at, *reminded) = (now, 0);
+                        }
+                        *last_activity = now;
+                    }
+                },
+                Err(_) => {},
+            }
+
+            let Some((started_at, last_activity, reminded)) = &mut playing else {
+                continue;
+            };
+            // don't nag someone who has been away for a while
+            if now - *last_activity > IDLE_RESET.as_secs() as i64 {
+                continue;
+            }
+            let Some(after) = get_reminder_after(&reminders, chrono::Local::now().weekday()) else {
+                continue;
+            };
+
+            let played = Duration::from_secs((now - *started_at).max(0) as u64);
+            if played < after * (*reminded + 1) {
+                continue;
+            }
+            *reminded += 1;
+
+            let text = format!(
+                "You've been playing for {}",
+                humantime::format_duration(Duration::from_secs(played.as_secs() / 60 * 60))
+            );
+            log::info!("Playtime reminder: {text}");
+            for action in &actions {
+                let result = task::block_in_place(|| {
+                    action.run("Time for a break?", &text, webhook.as_ref())
+                });
+                if let Err(e) = result {
+                    log::warn!("Failed to run playtime reminder action {action:?}: {e}");
+                }
+            }
+        }
+    }))
+}
@@ -0,0 +1,50 @@
+use anyhow::bail;
+use futures_util::StreamExt;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::events::GameEvent;
+use crate::supervisor;
+
+/// Receives the events of an agent tailing the game log on another machine, reconnecting
+/// whenever the connection drops
+pub async fn run_presenter(url: &str, token: Option<&str>, emit: &impl Fn(GameEvent)) {
+    let mut in_session = false;
+
+    supervisor::run("presenter", async || -> anyhow::Result<()> {
+        // the agent might have missed the end of the session while disconnected
+        if in_session {
+            emit(GameEvent::SessionEnded { ts: chrono::Utc::now().timestamp() });
+            in_session = false;
+        }
+
+        let mut request = url.into_client_request()?;
+        if let Some(token) = token {
+            request.headers_mut().insert(AUTHORIZATION, format!("Bearer {token}").parse()?);
+        }
+        let (mut socket, _) = tokio_tungstenite::connect_async(request).await?;
+        log::info!("Connected to agent at {url}");
+
+        while let Some(message) = socket.next().await {
+            let Message::Text(text) = message? else {
+                continue;
+            };
+
+            match serde_json::from_str::<GameEvent>(&text) {
+                Ok(event) => {
+                    match event {
+                        GameEvent::SessionStarted { .. } => in_session = true,
+                        GameEvent::SessionEnded { .. } => in_session = false,
+                        _ => {},
+                    }
+                    emit(event);
+                },
+                Err(e) => log::warn!("Skipping unknown event from agent: {e}"),
+            }
+        }
+
+        bail!("Agent at {url} closed the connection")
+    })
+    .await
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::config;
+use crate::game::Game;
+
+fn prompt(question: &str, default: &str) -> io::Result<String> {
+    match default.is_empty() {
+        true => print!("{question}: "),
+        false => print!("{question} [{default}]: "),
+    }
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(match answer.trim() {
+        "" => default.to_string(),
+        answer => answer.to_string(),
+    })
+}
+
+fn confirm(question: &str, default: bool) -> io::Result<bool> {
+    let answer = prompt(&format!("{question} (y/n)"), if default { "y" } else { "n" })?;
+    Ok(answer.to_lowercase().starts_with('y'))
+}
+
+/// Asks for the game directory and presence preferences on the console and writes them to the
+/// config file
+pub fn run_setup(config_file: &Path) -> anyhow::Result<()> {
+    println!("No {config_file:?} found, let's set things up (press enter to keep the default)");
+    let mut args = Vec::new();
+
+    let game = match prompt("Game, poe1 or poe2", "poe2")?.to_lowercase().as_str() {
+        "poe1" | "1" => Game::Poe1,
+        _ => Game::Poe2,
+    };
+    if game == Game::Poe1 {
+        args.extend(["--game".to_string(), "poe1".to_string()]);
+    }
+
+    let detected = game.get_default_directories().iter().find(|d| fs::metadata(d).is_ok());
+    if detected.is_none() {
+        println!("Couldn't find the game in the default install locations");
+    }
+    let game_dir = prompt("Game directory", detected.copied().unwrap_or_default())?;
+    if detected.is_none_or(|d| *d != game_dir) && !game_dir.is_empty() {
+        args.extend(["--game-dir".to_string(), game_dir]);
+    }
+
+    let character = prompt("Character to show when running several game instances", "")?;
+    if !character.is_empty() {
+        args.extend(["--primary-character".to_string(), character]);
+    }
+
+    println!("Privacy");
+    if let Ok(bracket @ 2..) = prompt("Round the level down to brackets of", "0")?.parse::<u16>() {
+        args.extend(["--level-bracket".to_string(), bracket.to_string()]);
+    }
+    if confirm("Show the instance seed", false)? {
+        args.push("--show-seed".to_string());
+    }
+    if confirm("Show the party size", false)? {
+        args.push("--party-hint".to_string());
+    }
+    if confirm("Show the trade whisper count", false)? {
+        args.push("--whisper-counter".to_string());
+    }
+    if !confirm("Record session history", true)? {
+        args.push("--no-history".to_string());
+    }
+
+    config::save_args(config_file, &args)?;
+    println!("Saved to {config_file:?}, edit it or delete it to run the setup again");
+    Ok(())
+}
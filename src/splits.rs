@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::events::GameEvent;
+use crate::models::ClassInfo;
+
+/// Area a fresh character spawns in, entering it with a fresh character starts a new run
+const RUN_START_CODE: &str = "G1_1";
+
+/// Campaign splits in run order, a split is completed when its area is entered for the first time.
+/// Boss splits use the boss arena as there is no log line for the kill itself.
+const SPLITS: &[(&str, &str)] = &[
+    ("G1_15", "Count Geonor"),
+    ("G2_1", "Act 2"),
+    ("G2_12_2", "Jamanra"),
+    ("G3_1", "Act 3"),
+    ("G3_17", "Doryani"),
+    ("C_G1_1", "Cruel Act 1"),
+    ("C_G1_15", "Cruel Count Geonor"),
+    ("C_G2_1", "Cruel Act 2"),
+    ("C_G2_12_2", "Cruel Jamanra"),
+    ("C_G3_1", "Cruel Act 3"),
+    ("C_G3_17", "Cruel Doryani"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitEvent {
+    Started,
+    Split,
+    Finished,
+}
+
+#[derive(Debug, Default)]
+pub struct SplitTimer {
+    started_at: Option<i64>,
+    completed: Vec<i64>,
+    quiet: bool,
+    /// Name and level of the character being played, None at character select or until known
+    character: Option<(String, u16)>,
+    run_character: Option<String>,
+    /// Characters that have been timed, going back to the first area with them isn't a new run
+    run_characters: HashSet<String>,
+}
+
+impl SplitTimer {
+    /// Timer that doesn't log its splits, for consumers running next to the presence timer
+    pub fn quiet() -> Self {
+        Self { quiet: true, ..Default::default() }
+    }
+
+    /// A character that can't have been through the campaign already, a new one spawns at level 1
+    /// and isn't known by name until its first level up
+    fn is_fresh_character(&self) -> bool {
+        self.character
+            .as_ref()
+            .is_none_or(|(name, level)| *level <= 1 || !self.run_characters.contains(name))
+    }
+
+    fn set_character(&mut self, character: Option<&ClassInfo>) {
+        self.character = character.map(|c| (c.username.clone(), c.level));
+        if self.run_character.is_none() && self.started_at.is_some() {
+            self.run_character = character.map(|c| c.username.clone());
+            self.run_characters.extend(self.run_character.clone());
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GameEvent) -> Option<SplitEvent> {
+        let area_info = match event {
+            GameEvent::SessionStarted { character, .. } => {
+                self.set_character(character.as_ref());
+                return None;
+            },
+            GameEvent::LevelUp { character: class_info, .. }
+            | GameEvent::CharacterUpdated(class_info) => {
+                self.set_character(Some(class_info));
+                return None;
+            },
+            GameEvent::CharacterSelect { .. } => {
+                self.character = None;
+                return None;
+            },
+            GameEvent::AreaChanged(area_info) => area_info,
+            _ => return None,
+        };
+
+        // re-entering the first area before the first split keeps the run going, walking back to
+        // it later in the run only restarts for a fresh character
+        let is_new_run = match self.started_at {
+            None => true,
+            Some(_) => !self.completed.is_empty() && self.is_fresh_character(),
+        };
+        if area_info.code == RUN_START_CODE && is_new_run {
+            if !self.quiet {
+                log::info!("Split timer started");
+            }
+            self.started_at = Some(area_info.ts);
+            self.completed.clear();
+            self.run_character = self.character.as_ref().map(|(name, _)| name.clone());
+            self.run_characters.extend(self.run_character.clone());
+            return Some(SplitEvent::Started);
+        }
+
+        let started_at = self.started_at?;
+        let (code, name) = SPLITS.get(self.completed.len())?;
+        if area_info.code != *code {
+            return None;
+        }
+
+        let segment_start = self.completed.last().copied().unwrap_or(started_at);
+        self.completed.push(area_info.ts);
+        if !self.quiet {
+            log::info!(
+                "Split {}/{} {name}: {} (segment {})",
+                self.completed.len(),
+                SPLITS.len(),
+                format_split(area_info.ts - started_at),
+                format_split(area_info.ts - segment_start)
+            );
+        }
+        match self.is_finished() {
+            true => {
+                if !self.quiet {
+                    log::info!("Campaign finished in {}", format_split(area_info.ts - started_at));
+                }
+                Some(SplitEvent::Finished)
+            },
+            false => Some(SplitEvent::Split),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.completed.len() == SPLITS.len()
+    }
+
+    /// Start of the run in progress, None if no run is being timed or the last one finished
+    pub fn get_run_start(&self) -> Option<i64> {
+        self.started_at.filter(|_| !self.is_finished())
+    }
+
+    /// Name of the split currently being run
+    pub fn get_current_split(&self) -> Option<&'static str> {
+        self.get_run_start()?;
+        SPLITS.get(self.completed.len()).map(|(_, name)| *name)
+    }
+}
+
+fn format_split(secs: i64) -> String {
+    humantime::format_duration(Duration::from_secs(secs.max(0) as u64)).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AreaKind, MapChangeInfo};
+
+    fn enter(timer: &mut SplitTimer, code: &str, ts: i64) -> Option<SplitEvent> {
+        let area_info = MapChangeInfo {
+            level: 1,
+            code: code.to_string(),
+            name: code.to_string(),
+            kind: AreaKind::Campaign,
+            seed: ts as u64,
+            ts,
+        };
+        timer.on_event(&GameEvent::AreaChanged(area_info))
+    }
+
+    fn level_up(timer: &mut SplitTimer, username: &str, level: u16) {
+        let class_info = ClassInfo::from_parts(username, "Warrior", level).unwrap();
+        timer.on_event(&GameEvent::LevelUp { character: class_info, ts: 0 });
+    }
+
+    #[test]
+    fn returning_to_the_first_area_mid_run_keeps_the_run() {
+        let mut timer = SplitTimer::quiet();
+        assert_eq!(enter(&mut timer, RUN_START_CODE, 100), Some(SplitEvent::Started));
+        level_up(&mut timer, "Runner", 2);
+        assert_eq!(enter(&mut timer, "G1_15", 200), Some(SplitEvent::Split));
+        level_up(&mut timer, "Runner", 12);
+
+        assert_eq!(enter(&mut timer, RUN_START_CODE, 300), None);
+        assert_eq!(timer.get_run_start(), Some(100));
+        assert_eq!(timer.get_current_split(), Some("Act 2"));
+    }
+
+    #[test]
+    fn fresh_character_starts_a_new_run() {
+        let mut timer = SplitTimer::quiet();
+        enter(&mut timer, RUN_START_CODE, 100);
+        level_up(&mut timer, "Runner", 2);
+        enter(&mut timer, "G1_15", 200);
+
+        // a new character isn't known by name until it levels up
+        timer.on_event(&GameEvent::CharacterSelect { ts: 300 });
+        assert_eq!(enter(&mut timer, RUN_START_CODE, 400), Some(SplitEvent::Started));
+        assert_eq!(timer.get_run_start(), Some(400));
+
+        level_up(&mut timer, "Runner2", 2);
+        enter(&mut timer, "G1_15", 500);
+        // switching back to a character that was timed before isn't a fresh one
+        level_up(&mut timer, "Runner", 13);
+        assert_eq!(enter(&mut timer, RUN_START_CODE, 600), None);
+        assert_eq!(timer.get_run_start(), Some(400));
+    }
+}
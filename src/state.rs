@@ -0,0 +1,244 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use serde::Serialize;
+
+use crate::events::GameEvent;
+use crate::models::{AreaKind, ClassInfo, MapChangeInfo};
+
+const MAX_AREA_HISTORY: usize = 25;
+
+/// Locks the shared session state, recovering it if a worker panicked while holding the lock so
+/// the other workers and the restarted one keep going
+pub fn lock(state: &Mutex<SessionState>) -> MutexGuard<'_, SessionState> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionStats {
+    pub started_at: i64,
+    pub areas_visited: u32,
+    pub levels_gained: u32,
+    pub deaths: u32,
+    pub trade_whispers: u32,
+    pub maps_completed: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelProgress {
+    pub level: u16,
+    pub ts: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionState {
+    pub game_running: bool,
+    pub discord_connected: bool,
+    pub game_version: Option<String>,
+    pub gateway: Option<String>,
+    pub server: Option<String>,
+    pub latency_ms: Option<u32>,
+    pub league: Option<String>,
+    pub note: Option<String>,
+    pub character: Option<ClassInfo>,
+    pub area: Option<MapChangeInfo>,
+    /// Since when the player is at the login or character select screen
+    pub character_select_since: Option<i64>,
+    pub party: Vec<String>,
+    /// Fresh boss arena instances entered this session by arena code
+    pub boss_attempts: HashMap<String, u32>,
+    pub area_history: Vec<MapChangeInfo>,
+    pub level_history: Vec<LevelProgress>,
+    pub stats: SessionStats,
+    #[serde(skip)]
+    seen_trade_whispers: HashSet<(String, String)>,
+    #[serde(skip)]
+    completed_maps: HashSet<(String, u64)>,
+    #[serde(skip)]
+    boss_instances: HashSet<(String, u64)>,
+}
+
+impl SessionState {
+    pub fn new() -> Self {
+        Self {
+            stats: SessionStats {
+                started_at: chrono::Utc::now().timestamp(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn apply(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::SessionStarted { ts, character } => {
+                // the note is set by the user, not the game, keep it across sessions
+                let note = self.note.take();
+                *self = Self::new();
+                self.note = note;
+                self.game_running = true;
+                self.stats.started_at = *ts;
+                if let Some(class_info) = character {
+                    self.set_character(class_info, *ts);
+                }
+            },
+            GameEvent::SessionEnded { .. } => self.game_running = false,
+            GameEvent::LevelUp { character, ts } => self.set_character(character, *ts),
+            GameEvent::CharacterUpdated(class_info) => self.update_character(class_info),
+            GameEvent::AreaChanged(area_info) => self.set_area(area_info),
+            GameEvent::CharacterSelect { ts } => {
+                // whoever is picked next starts over, even if it's the same character again
+                self.character = None;
+                self.clear_character_stats();
+                self.area = None;
+                self.party.clear();
+                self.character_select_since.get_or_insert(*ts);
+            },
+            GameEvent::PlayerJoined { username } => self.player_joined(username),
+            GameEvent::PlayerLeft { username } => self.player_left(username),
+            GameEvent::Death { username, .. } => {
+                if self.character.as_ref().is_some_and(|c| &c.username == username) {
+                    self.stats.deaths += 1;
+                }
+            },
+            GameEvent::Whisper { from, message, is_trade: true } => {
+                if self.seen_trade_whispers.insert((from.clone(), message.clone())) {
+                    self.stats.trade_whispers += 1;
+                }
+            },
+            GameEvent::Whisper { .. } => {},
+            GameEvent::GameVersion { version } => {
+                log::info!("Game version {version}");
+                self.game_version = Some(version.clone());
+            },
+            GameEvent::ServerConnected { address, gateway, latency_ms } => {
+                if gateway.is_some() && gateway != &self.gateway {
+                    log::info!("Connected to gateway {}", gateway.as_deref().unwrap_or_default());
+                    self.gateway = gateway.clone();
+                }
+                self.server = Some(address.clone());
+                if latency_ms.is_some() {
+                    self.latency_ms = *latency_ms;
+                }
+            },
+            GameEvent::League { name } => self.league = Some(name.clone()),
+            GameEvent::NoteChanged { note } => self.note = note.clone(),
+        }
+    }
+
+    pub fn set_character(&mut self, class_info: &ClassInfo, ts: i64) {
+        let is_switch = self.character.as_ref().is_none_or(|c| c.username != class_info.username);
+        if is_switch {
+            if let Some(previous) = &self.character {
+                log::info!(
+                    "Switched character from {} to {}",
+                    previous.username,
+                    class_info.username
+                );
+            }
+            self.clear_character_stats();
+        } else if self.character.as_ref().is_some_and(|c| c.level < class_info.level) {
+            self.stats.levels_gained += 1;
+        }
+
+        self.level_history.push(LevelProgress { level: class_info.level, ts });
+        self.character = Some(class_info.clone());
+    }
+
+    /// Deaths and levels belong to the character, they aren't carried over to the next one
+    fn clear_character_stats(&mut self) {
+        self.level_history.clear();
+        self.stats.deaths = 0;
+        self.stats.levels_gained = 0;
+    }
+
+    /// Takes the class and level of the current character without counting it as a level up
+    pub fn update_character(&mut self, class_info: &ClassInfo) {
+        match &mut self.character {
+            Some(character) if character.username == class_info.username => {
+                *character = class_info.clone();
+            },
+            _ => self.set_character(class_info, chrono::Utc::now().timestamp()),
+        }
+    }
+
+    pub fn set_area(&mut self, area_info: &MapChangeInfo) {
+        self.stats.areas_visited += 1;
+
+        // a map counts once it's left for another instance, going back in through a portal
+        // doesn't count it again
+        if let Some(previous) = self.area.as_ref().filter(|a| a.kind == AreaKind::Map) {
+            if !previous.is_same_instance(area_info)
+                && self.completed_maps.insert((previous.code.clone(), previous.seed))
+            {
+                self.stats.maps_completed += 1;
+            }
+        }
+        self.party.clear();
+
+        if self.area_history.len() == MAX_AREA_HISTORY {
+            self.area_history.remove(0);
+        }
+        self.area_history.push(area_info.clone());
+        if area_info.kind == AreaKind::BossArena
+            && self.boss_instances.insert((area_info.code.clone(), area_info.seed))
+        {
+            let attempts = self.boss_attempts.entry(area_info.code.clone()).or_default();
+            *attempts += 1;
+            log::info!("Boss arena {} attempt {attempts}", area_info.name);
+        }
+        self.area = Some(area_info.clone());
+        self.character_select_since = None;
+    }
+
+    pub fn player_joined(&mut self, username: &str) {
+        if !self.party.iter().any(|p| p == username) {
+            self.party.push(username.to_owned());
+        }
+    }
+
+    pub fn player_left(&mut self, username: &str) {
+        self.party.retain(|p| p != username);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn recovers_the_state_after_a_panic_while_locked() {
+        let state = Arc::new(Mutex::new(SessionState::default()));
+
+        let locked = Arc::clone(&state);
+        let panicked = thread::spawn(move || {
+            let _state = lock(&locked);
+            panic!("worker panicked while holding the state");
+        })
+        .join();
+        assert!(panicked.is_err() && state.is_poisoned());
+
+        lock(&state).apply(&GameEvent::GameVersion { version: "0.1.0".to_string() });
+        assert_eq!(lock(&state).game_version.as_deref(), Some("0.1.0"));
+    }
+
+    #[test]
+    fn character_select_clears_the_character() {
+        let character = ClassInfo::from_parts("Tester", "Witch", 10).unwrap();
+        let mut state = SessionState::new();
+        state.apply(&GameEvent::SessionStarted { ts: 0, character: Some(character.clone()) });
+        state.apply(&GameEvent::LevelUp {
+            character: ClassInfo { level: 11, ..character.clone() },
+            ts: 10,
+        });
+        state.apply(&GameEvent::Death { username: "Tester".to_string(), ts: 20 });
+        assert_eq!((state.stats.levels_gained, state.stats.deaths), (1, 1));
+
+        state.apply(&GameEvent::CharacterSelect { ts: 30 });
+        assert!(state.character.is_none() && state.level_history.is_empty());
+        assert_eq!((state.stats.levels_gained, state.stats.deaths), (0, 0));
+    }
+}
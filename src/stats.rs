@@ -0,0 +1,95 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::bail;
+use rusqlite::{params, Connection};
+
+/// End of a session, falls back to the last recorded area visit for sessions that never ended cleanly
+const SESSION_END: &str = "COALESCE(s.ended_at, (SELECT MAX(COALESCE(v.left_at, v.entered_at)) \
+                           FROM area_visits v WHERE v.session_id = s.id), s.started_at)";
+const VISIT_DURATION: &str = "COALESCE(left_at, entered_at) - entered_at";
+
+fn format_secs(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s.max(0)),
+        s => humantime::format_duration(Duration::from_secs((s / 60 * 60) as u64)).to_string(),
+    }
+}
+
+fn print_playtime(conn: &Connection, title: &str, format: &str, days: u32) -> anyhow::Result<()> {
+    println!("{title}");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT strftime('{format}', s.started_at, 'unixepoch', 'localtime') AS period, \
+         SUM({SESSION_END} - s.started_at) FROM sessions s \
+         WHERE s.started_at >= CAST(strftime('%s', 'now', ?1) AS INTEGER) GROUP BY period ORDER BY period"
+    ))?;
+    let rows = stmt.query_map(params![format!("-{days} days")], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+    for row in rows {
+        let (period, secs) = row?;
+        println!("  {period:<24} {}", format_secs(secs));
+    }
+    Ok(())
+}
+
+pub fn print_stats(db: &Path, days: u32) -> anyhow::Result<()> {
+    if !db.exists() {
+        bail!("No history recorded yet at {db:?}");
+    }
+    let conn = Connection::open(db)?;
+
+    print_playtime(&conn, &format!("Playtime per day (last {days} days)"), "%Y-%m-%d", days)?;
+    println!();
+    print_playtime(&conn, &format!("Playtime per week (last {days} days)"), "%Y week %W", days)?;
+    println!();
+
+    println!("Playtime per character");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT character, SUM({VISIT_DURATION}) AS secs FROM area_visits \
+         WHERE character IS NOT NULL GROUP BY character ORDER BY secs DESC"
+    ))?;
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))? {
+        let (character, secs) = row?;
+        println!("  {character:<24} {}", format_secs(secs));
+    }
+    println!();
+
+    println!("Most visited zones");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT name, COUNT(*) AS visits, SUM({VISIT_DURATION}) FROM area_visits \
+         GROUP BY code ORDER BY visits DESC LIMIT 10"
+    ))?;
+    for row in stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+    })? {
+        let (name, visits, secs) = row?;
+        println!("  {name:<32} {visits:>5} visits  {}", format_secs(secs));
+    }
+    println!();
+
+    println!("Levelling pace");
+    let mut stmt = conn.prepare(
+        "SELECT character, MIN(level), MAX(level), MIN(ts), MAX(ts) FROM level_ups \
+         GROUP BY character ORDER BY MAX(ts) DESC",
+    )?;
+    for row in stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, u16>(1)?,
+            row.get::<_, u16>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, i64>(4)?,
+        ))
+    })? {
+        let (character, min_level, max_level, first_ts, last_ts) = row?;
+        let levels = i64::from(max_level - min_level);
+        let pace = match levels {
+            0 => "-".to_string(),
+            _ => format!("{} per level", format_secs((last_ts - first_ts) / levels)),
+        };
+        println!("  {character:<24} level {min_level:>3} -> {max_level:<3} {pace}");
+    }
+
+    Ok(())
+}
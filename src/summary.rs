@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::events::{EventBus, GameEvent};
+use crate::models::AreaKind;
+use crate::supervisor;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AreaVisit {
+    pub code: String,
+    pub name: String,
+    pub kind: AreaKind,
+    pub area_level: u16,
+    pub character_level: Option<u16>,
+    pub entered_at: i64,
+    pub left_at: Option<i64>,
+    pub duration_secs: Option<i64>,
+    pub deaths: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionSummary {
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+    pub character: Option<String>,
+    pub start_level: Option<u16>,
+    pub end_level: Option<u16>,
+    pub levels_gained: u32,
+    pub deaths: u32,
+    pub trade_whispers: u32,
+    pub visits: Vec<AreaVisit>,
+}
+
+impl SessionSummary {
+    pub fn apply(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::SessionStarted { ts, character } => {
+                *self = Self {
+                    started_at: *ts,
+                    character: character.as_ref().map(|c| c.username.clone()),
+                    start_level: character.as_ref().map(|c| c.level),
+                    end_level: character.as_ref().map(|c| c.level),
+                    ..Default::default()
+                };
+            },
+            GameEvent::SessionEnded { ts } => {
+                self.leave_area(*ts);
+                self.ended_at = Some(*ts);
+            },
+            GameEvent::AreaChanged(area_info) => {
+                self.leave_area(area_info.ts);
+                self.visits.push(AreaVisit {
+                    code: area_info.code.clone(),
+                    name: area_info.name.clone(),
+                    kind: area_info.kind,
+                    area_level: area_info.level,
+                    character_level: self.end_level,
+                    entered_at: area_info.ts,
+                    left_at: None,
+                    duration_secs: None,
+                    deaths: 0,
+                });
+            },
+            GameEvent::CharacterSelect { ts } => self.leave_area(*ts),
+            GameEvent::CharacterUpdated(class_info) => {
+                if self.character.as_ref() != Some(&class_info.username) {
+                    self.character = Some(class_info.username.clone());
+                    self.start_level = Some(class_info.level);
+                }
+                self.end_level = Some(class_info.level);
+            },
+            GameEvent::LevelUp { character: class_info, .. } => {
+                if self.character.as_ref() == Some(&class_info.username) {
+                    if self.end_level.is_some_and(|l| l < class_info.level) {
+                        self.levels_gained += 1;
+                    }
+                } else {
+                    self.character = Some(class_info.username.clone());
+                    self.start_level = Some(class_info.level);
+                }
+                self.end_level = Some(class_info.level);
+            },
+            GameEvent::Death { username, .. } if self.character.as_ref() == Some(username) => {
+                self.deaths += 1;
+                if let Some(visit) = self.visits.last_mut() {
+                    visit.deaths += 1;
+                }
+            },
+            GameEvent::Whisper { is_trade: true, .. } => self.trade_whispers += 1,
+            _ => {},
+        }
+    }
+
+    pub fn duration_secs(&self) -> i64 {
+        self.ended_at.unwrap_or_else(|| chrono::Utc::now().timestamp()) - self.started_at
+    }
+
+    pub fn highest_map(&self) -> Option<(u16, &AreaVisit)> {
+        self.visits
+            .iter()
+            .filter_map(|v| v.kind.get_map_tier(&v.code, v.area_level).map(|tier| (tier, v)))
+            .max_by_key(|(tier, _)| *tier)
+    }
+
+    pub fn log(&self) {
+        let length = Duration::from_secs(self.duration_secs().max(0) as u64);
+        log::info!("Session summary:");
+        log::info!("  Length: {}", humantime::format_duration(length));
+        log::info!("  Zones visited: {}", self.visits.len());
+        if let Some((tier, visit)) = self.highest_map() {
+            log::info!("  Highest tier map: T{tier} {}", visit.name);
+        }
+        match (self.start_level, self.end_level) {
+            (Some(start), Some(end)) if self.levels_gained > 0 => {
+                log::info!("  Levels gained: {} ({start} -> {end})", self.levels_gained)
+            },
+            _ => log::info!("  Levels gained: {}", self.levels_gained),
+        }
+        log::info!("  Deaths: {}", self.deaths);
+        log::info!("  Trade whispers: {}", self.trade_whispers);
+    }
+
+    fn leave_area(&mut self, ts: i64) {
+        if let Some(visit) = self.visits.last_mut().filter(|v| v.left_at.is_none()) {
+            visit.left_at = Some(ts);
+            visit.duration_secs = Some(ts - visit.entered_at);
+        }
+    }
+}
+
+pub fn spawn_reporter(bus: &EventBus) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("session reporter", async move || -> anyhow::Result<()> {
+        let mut summary = SessionSummary::default();
+
+        while let Some(event) = rx.recv().await {
+            summary.apply(&event);
+
+            if let GameEvent::SessionEnded { .. } = event {
+                summary.log();
+            }
+        }
+        Ok(())
+    }))
+}
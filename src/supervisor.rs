@@ -0,0 +1,45 @@
+use std::any::Any;
+use std::fmt::Display;
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
+
+use futures_util::FutureExt;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Runs lasting longer than this count as recovered and reset the backoff
+const HEALTHY_RUN: Duration = Duration::from_secs(60);
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Runs `f` until it returns successfully, restarting it with exponential backoff whenever it
+/// fails or panics
+pub async fn run<F, E>(name: &str, mut f: F)
+where
+    F: AsyncFnMut() -> Result<(), E>,
+    E: Display,
+{
+    let mut backoff = MIN_BACKOFF;
+
+    loop {
+        let started_at = Instant::now();
+        match AssertUnwindSafe(f()).catch_unwind().await {
+            Ok(Ok(())) => return,
+            Ok(Err(e)) => log::error!("{name} failed: {e}"),
+            Err(panic) => log::error!("{name} panicked: {}", panic_message(panic.as_ref())),
+        }
+
+        if started_at.elapsed() > HEALTHY_RUN {
+            backoff = MIN_BACKOFF;
+        }
+        log::info!("Restarting {name} in {}", humantime::format_duration(backoff));
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
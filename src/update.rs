@@ -0,0 +1,96 @@
+use std::path::Path;
+use std::{env, fs, io};
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/LeagueRaINi/poe2-discord-rpc/releases/latest";
+
+#[derive(Debug, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    assets: Vec<ReleaseAsset>,
+}
+
+impl Release {
+    fn get_version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    fn is_newer(&self) -> bool {
+        parse_version(self.get_version()) > parse_version(env!("CARGO_PKG_VERSION"))
+    }
+}
+
+fn parse_version(version: &str) -> Vec<u64> {
+    version.split(['.', '-']).map_while(|part| part.parse().ok()).collect()
+}
+
+fn fetch_latest_release() -> anyhow::Result<Release> {
+    Ok(ureq::get(LATEST_RELEASE_URL)
+        .set("User-Agent", concat!("poe2-rpc/", env!("CARGO_PKG_VERSION")))
+        .set("Accept", "application/vnd.github+json")
+        .call()?
+        .into_json()?)
+}
+
+pub fn spawn_update_check() {
+    tokio::task::spawn_blocking(|| match fetch_latest_release() {
+        Ok(release) if release.is_newer() => log::warn!(
+            "A newer version {} is available at {}, run `poe2-rpc self-update` to install it",
+            release.get_version(),
+            release.html_url
+        ),
+        Ok(_) => log::debug!("Running the latest version"),
+        Err(e) => log::debug!("Failed to check for updates: {e}"),
+    });
+}
+
+pub fn self_update() -> anyhow::Result<()> {
+    let release = fetch_latest_release()?;
+    if !release.is_newer() {
+        log::info!("Already running the latest version {}", env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+
+    if !cfg!(windows) {
+        bail!("Releases only ship windows binaries, update from {} instead", release.html_url);
+    }
+    let asset = release
+        .assets
+        .iter()
+        .find(|a| a.name.ends_with(".exe"))
+        .with_context(|| format!("Release {} has no binary attached", release.tag_name))?;
+
+    let exe = env::current_exe()?;
+    let new_exe = exe.with_extension("new");
+    log::info!("Downloading {} from {}", asset.name, asset.browser_download_url);
+    let mut reader = ureq::get(&asset.browser_download_url).call()?.into_reader();
+    io::copy(&mut reader, &mut fs::File::create(&new_exe)?)?;
+
+    replace_exe(&exe, &new_exe)?;
+    log::info!("Updated to {}, restart to use the new version", release.get_version());
+    Ok(())
+}
+
+/// Swaps the running binary, windows doesn't allow overwriting it but allows renaming it
+fn replace_exe(exe: &Path, new_exe: &Path) -> io::Result<()> {
+    let old_exe = exe.with_extension("old");
+    if old_exe.exists() {
+        fs::remove_file(&old_exe)?;
+    }
+    fs::rename(exe, &old_exe)?;
+    if let Err(e) = fs::rename(new_exe, exe) {
+        fs::rename(&old_exe, exe)?;
+        return Err(e);
+    }
+    Ok(())
+}
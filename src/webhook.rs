@@ -0,0 +1,125 @@
+use serde_json::{json, Value};
+use tokio::task::{self, JoinHandle};
+
+use crate::events::{EventBus, GameEvent};
+use crate::game::Game;
+use crate::last_presence::ActProgress;
+use crate::models::{ClassInfo, MapChangeInfo};
+use crate::supervisor;
+
+const COLOR_LEVEL_UP: u32 = 0xc8a14b;
+const COLOR_DEATH: u32 = 0xa94442;
+const COLOR_ACT: u32 = 0x5cb85c;
+
+/// Discord webhook, posting as the game being played
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+    pub game: Game,
+}
+
+impl Webhook {
+    /// Posts a single embed to the webhook
+    pub fn post_embed(&self, embed: Value) -> Result<(), Box<ureq::Error>> {
+        let payload = json!({
+            "username": self.game.get_name(),
+            "embeds": [embed],
+        });
+        log::trace!("Sending webhook: {payload}");
+
+        ureq::post(&self.url).send_json(payload).map(|_| ()).map_err(Box::new)
+    }
+}
+
+/// Posts level ups, deaths and acts the character hasn't been to before, `act_progress` is what
+/// was saved by earlier runs
+pub fn spawn_notifier(
+    webhook: Webhook,
+    min_level: u16,
+    mut act_progress: ActProgress,
+    bus: &EventBus,
+) -> JoinHandle<()> {
+    let mut rx = bus.subscribe();
+
+    tokio::spawn(supervisor::run("webhook notifier", async move || -> anyhow::Result<()> {
+        let mut character: Option<ClassInfo> = None;
+        let mut area: Option<MapChangeInfo> = None;
+
+        while let Some(event) = rx.recv().await {
+            let embed = match event {
+                GameEvent::SessionStarted { character: class_info, .. } => {
+                    character = class_info;
+                    area = None;
+                    None
+                },
+                GameEvent::LevelUp { character: class_info, .. } => {
+                    let embed = (class_info.level >= min_level).then(|| {
+                        let class = class_info
+                            .ascendency
+                            .as_ref()
+                            .map_or(class_info.class.to_string(), |a| a.to_string());
+                        json!({
+                            "title": format!("{} reached level {}", class_info.username, class_info.level),
+                            "description": class,
+                            "color": COLOR_LEVEL_UP,
+                        })
+                    });
+                    character = Some(class_info);
+                    embed
+                },
+                GameEvent::CharacterUpdated(class_info) => {
+                    character = Some(class_info);
+                    None
+                },
+                // deaths and acts aren't posted for whoever was played before the switch
+                GameEvent::CharacterSelect { .. } => {
+                    character = None;
+                    area = None;
+                    None
+                },
+                // party members die too, only the played character's deaths are posted
+                GameEvent::Death { username, .. }
+                    if character.as_ref().is_some_and(|c| c.username == username) =>
+                {
+                    let description = area.as_ref().map(|a| format!("in {} ({})", a.name, a.level));
+                    Some(json!({
+                        "title": format!("{username} has been slain"),
+                        "description": description,
+                        "color": COLOR_DEATH,
+                    }))
+                },
+                GameEvent::AreaChanged(area_info) => {
+                    let is_first_area = area.is_none();
+                    let act = character.as_ref().and_then(|c| {
+                        // without earlier progress, the act the session starts in may not be new
+                        let is_unknown_start = is_first_area && !act_progress.is_known(&c.username);
+                        act_progress
+                            .reach(&c.username, &area_info)
+                            .filter(|_| !is_unknown_start)
+                            .map(|act| (c.username.as_str(), act))
+                    });
+                    area = Some(area_info);
+
+                    match act {
+                        Some((name, (is_cruel, act))) => Some(json!({
+                            "title": match is_cruel {
+                                true => format!("{name} reached Cruel Act {act}"),
+                                false => format!("{name} reached Act {act}"),
+                            },
+                            "color": COLOR_ACT,
+                        })),
+                        _ => None,
+                    }
+                },
+                _ => None,
+            };
+
+            if let Some(embed) = embed {
+                if let Err(e) = task::block_in_place(|| webhook.post_embed(embed)) {
+                    log::warn!("Failed to send webhook: {e}");
+                }
+            }
+        }
+        Ok(())
+    }))
+}
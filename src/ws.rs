@@ -0,0 +1,145 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, State};
+use axum::http::header::{AUTHORIZATION, ORIGIN};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use tokio::net::TcpListener;
+
+use crate::events::{EventBus, GameEvent};
+use crate::state::{self, SessionState};
+
+#[derive(Clone)]
+struct ServerState {
+    bus: EventBus,
+    state: Arc<Mutex<SessionState>>,
+    token: Option<String>,
+}
+
+/// Streams the events to websocket clients, only to those sending the token as a bearer token if
+/// one is set
+pub async fn spawn_event_server(
+    addr: SocketAddr,
+    token: Option<String>,
+    bus: EventBus,
+    state: Arc<Mutex<SessionState>>,
+) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    log::info!("Broadcasting events on ws://{addr}");
+
+    let app = Router::new().route("/", get(upgrade)).with_state(ServerState { bus, state, token });
+
+    tokio::spawn(async move {
+        let app = app.into_make_service_with_connect_info::<SocketAddr>();
+        if let Err(e) = axum::serve(listener, app).await {
+            log::warn!("Websocket server stopped: {e}");
+        }
+    });
+
+    Ok(())
+}
+
+/// Whether the upgrade comes from a local page or a client that isn't a browser, browsers don't
+/// apply cors to websockets so any website could connect otherwise
+fn is_local_origin(origin: Option<&str>) -> bool {
+    let Some(origin) = origin.filter(|origin| *origin != "null") else {
+        return true;
+    };
+    let host = origin.split_once("://").map_or(origin, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(host) => host.split(']').next(),
+        None => host.split([':', '/']).next(),
+    };
+    host.is_some_and(|host| {
+        host == "localhost" || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+    })
+}
+
+async fn upgrade(
+    ws: WebSocketUpgrade,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    State(ServerState { bus, state, token }): State<ServerState>,
+) -> Response {
+    let origin = headers.get(ORIGIN).map(|origin| origin.to_str().unwrap_or_default());
+    if !is_local_origin(origin) {
+        log::warn!("Rejected websocket client {peer} from {}", origin.unwrap_or_default());
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if let Some(token) = token {
+        let auth = headers.get(AUTHORIZATION).and_then(|auth| auth.to_str().ok());
+        if auth.and_then(|auth| auth.strip_prefix("Bearer ")) != Some(token.as_str()) {
+            log::warn!("Rejected websocket client {peer} without the token");
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+
+    ws.on_upgrade(move |socket| async move {
+        log::info!("Websocket client connected: {peer}");
+        if let Err(e) = handle_client(socket, bus, state).await {
+            log::debug!("Websocket client disconnected: {e}");
+        }
+    })
+}
+
+/// Events recreating the running session for clients connecting in the middle of it
+fn get_session_snapshot(state: &SessionState) -> Vec<GameEvent> {
+    if !state.game_running {
+        return Vec::new();
+    }
+
+    let mut events = vec![GameEvent::SessionStarted {
+        ts: state.stats.started_at,
+        character: state.character.clone(),
+    }];
+    events.extend(state.game_version.clone().map(|version| GameEvent::GameVersion { version }));
+    events.extend(state.server.clone().map(|address| GameEvent::ServerConnected {
+        address,
+        gateway: state.gateway.clone(),
+        latency_ms: state.latency_ms,
+    }));
+    events.extend(state.league.clone().map(|name| GameEvent::League { name }));
+    events.extend(state.area.clone().map(GameEvent::AreaChanged));
+    events.extend(state.character_select_since.map(|ts| GameEvent::CharacterSelect { ts }));
+    events
+}
+
+async fn handle_client(
+    mut socket: WebSocket,
+    bus: EventBus,
+    state: Arc<Mutex<SessionState>>,
+) -> anyhow::Result<()> {
+    let mut rx = bus.subscribe();
+    let snapshot = get_session_snapshot(&state::lock(&state));
+
+    for event in snapshot {
+        socket.send(Message::Text(serde_json::to_string(&event)?.into())).await?;
+    }
+    while let Some(event) = rx.recv().await {
+        socket.send(Message::Text(serde_json::to_string(&event)?.into())).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_local_origins_only() {
+        for origin in [None, Some("null"), Some("http://127.0.0.1:7820"), Some("http://[::1]:7820")]
+        {
+            assert!(is_local_origin(origin), "{origin:?}");
+        }
+        assert!(is_local_origin(Some("http://localhost")));
+        for origin in ["https://example.com", "http://localhost.example.com", "http://192.168.1.10"]
+        {
+            assert!(!is_local_origin(Some(origin)), "{origin}");
+        }
+    }
+}